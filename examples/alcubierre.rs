@@ -0,0 +1,80 @@
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame},
+    metric::NumericMetric,
+    objects::{RayIntersector, SphereCollider},
+    render::{RenderSettings, render_scene},
+    spectrum::Emission,
+};
+use image::Rgb;
+
+fn main() {
+    // Alcubierre warp bubble of radius R moving along x with velocity v
+    let (v, radius, sigma) = (0.9, 2.0, 4.0);
+    let metric = NumericMetric::new(move |p: FourVector| {
+        let [t, x, y, z] = p.0;
+        let rs = ((x - v * t).powi(2) + y * y + z * z).sqrt();
+        let f = ((sigma * (rs + radius)).tanh() - (sigma * (rs - radius)).tanh())
+            / (2.0 * (sigma * radius).tanh());
+        [
+            [-(1.0 - v * v * f * f), -v * f, 0.0, 0.0],
+            [-v * f, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+    });
+
+    // Camera riding along in the center of the bubble, looking forward
+    let camera_frame = ManifoldFrame {
+        root: Coord {
+            components: FourVector([0.0, 0.0, 0.0, 0.0]),
+            _metric: std::marker::PhantomData,
+        },
+        axis: [
+            FourVector([1.0, v, 0.0, 0.0]),
+            FourVector([0.0, 0.0, 1.0, 0.0]),
+            FourVector([0.0, 0.0, 0.0, 1.0]),
+            FourVector([0.0, 1.0, 0.0, 0.0]),
+        ],
+    }
+    .normalize(&metric);
+
+    let mut objects: Vec<(Emission, Box<dyn RayIntersector<_>>)> = vec![];
+    for y in [-3.0, 0.0, 3.0] {
+        for z in [-3.0, 0.0, 3.0] {
+            let center = ManifoldFrame {
+                root: Coord {
+                    components: FourVector([0.0, 10.0, y, z]),
+                    _metric: std::marker::PhantomData,
+                },
+                axis: [
+                    FourVector([1.0, 0.0, 0.0, 0.0]),
+                    FourVector([0.0, 1.0, 0.0, 0.0]),
+                    FourVector([0.0, 0.0, 1.0, 0.0]),
+                    FourVector([0.0, 0.0, 0.0, 1.0]),
+                ],
+            }
+            .normalize(&metric);
+            objects.push((
+                Rgb([255, 255, 255]).into(),
+                Box::new(SphereCollider {
+                    center,
+                    radius: 0.8,
+                    time_thickness: 1000.0,
+                }),
+            ));
+        }
+    }
+
+    let image = render_scene(
+        &metric,
+        BasicCamera::new(camera_frame, 200, 200, 2.0),
+        objects,
+        BoundingBox {
+            bbox: [[-20.0, 1.0], [-5.0, 15.0], [-10.0, 10.0], [-10.0, 10.0]],
+            _metric: std::marker::PhantomData,
+        },
+        RenderSettings::new(0.1),
+    );
+    image.save("alcubierre.png").unwrap();
+}
@@ -0,0 +1,106 @@
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame, SpatialVec},
+    metric::CarthesianMinkowski,
+    objects::{DiscCollider, PlaneCollider, RayIntersector, SphereCollider},
+    render::{RenderSettings, render_scene},
+    spectrum::Emission,
+    texture::{Checkerboard, ClockFace, LatLongGrid, Mapping, Textured},
+};
+use image::Rgb;
+
+/// Two rows of synchronized clocks over a checkered floor: the top row at
+/// rest, the bottom row moving to the right at 0.6c. The moving clocks run
+/// slow, and those further ahead lag behind, as clocks synchronized in one
+/// frame are not in another. Next to them a globe shows the lat-long grid.
+fn main() {
+    let beta: f64 = 0.6;
+    let gamma = 1.0 / (1.0 - beta * beta).sqrt();
+    let frame = |position: [f64; 3], beta: f64| {
+        let root = Coord {
+            components: FourVector([0.0, position[0], position[1], position[2]]),
+            _metric: std::marker::PhantomData,
+        };
+        ManifoldFrame::from_four_velocity(
+            &CarthesianMinkowski,
+            root,
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+        )
+        .boosted(&CarthesianMinkowski, SpatialVec([beta, 0.0, 0.0]))
+    };
+    let clock = ClockFace {
+        radius: 1.0,
+        period: 40.0,
+        face: Rgb([240, 240, 220]).into(),
+        marks: Rgb([20, 20, 20]).into(),
+    };
+
+    let mut objects: Vec<(Emission, Box<dyn RayIntersector<_>>)> = vec![
+        (
+            Rgb([0, 0, 0]).into(),
+            Box::new(Textured::new(
+                PlaneCollider {
+                    frame: frame([0.0, 4.0, 0.0], 0.0),
+                    normal: SpatialVec([0.0, 1.0, 0.0]),
+                    time_thickness: 1000.0,
+                },
+                Checkerboard {
+                    mapping: Mapping::Planar { size: 2.0 },
+                    colors: [Rgb([200, 200, 200]).into(), Rgb([60, 60, 60]).into()],
+                },
+            )),
+        ),
+        (
+            Rgb([0, 0, 0]).into(),
+            Box::new(Textured::new(
+                SphereCollider {
+                    center: frame([7.0, 0.0, 20.0], 0.0),
+                    radius: 2.5,
+                    time_thickness: 1000.0,
+                },
+                LatLongGrid {
+                    spacing: 15f64.to_radians(),
+                    width: 2f64.to_radians(),
+                    line: Rgb([255, 255, 255]).into(),
+                    fill: Rgb([40, 90, 200]).into(),
+                },
+            )),
+        ),
+    ];
+    for i in 0..4 {
+        let x = -9.0 + 2.5 * i as f64;
+        for (y, beta) in [(-1.5, 0.0), (1.5, beta)] {
+            // Place the moving clocks so that they pass x at the time their
+            // light reaches the camera
+            let x = x + beta * 20.0;
+            objects.push((
+                Rgb([0, 0, 0]).into(),
+                Box::new(Textured::new(
+                    DiscCollider {
+                        frame: frame([x, y, 20.0], beta),
+                        inner_radius: 0.0,
+                        outer_radius: 1.0,
+                        time_thickness: 1000.0,
+                    },
+                    clock.clone(),
+                )),
+            ));
+        }
+    }
+    println!("moving clocks run slow by a factor {gamma:.2}");
+
+    let mut settings = RenderSettings::new(0.5);
+    settings.doppler = false;
+
+    let image = render_scene(
+        &CarthesianMinkowski,
+        BasicCamera::new(frame([0.0, 0.0, 0.0], 0.0), 400, 250, 1.0),
+        objects,
+        BoundingBox {
+            bbox: [[-60.0, 1.0], [-40.0, 40.0], [-20.0, 20.0], [-1.0, 50.0]],
+            _metric: std::marker::PhantomData,
+        },
+        settings,
+    );
+    image.save("clocks.png").unwrap();
+}
@@ -0,0 +1,95 @@
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame, SpatialVec},
+    metric::CarthesianMinkowski,
+    objects::{BoxCollider, Csg, CylinderCollider, RayIntersector, SphereCollider, TorusCollider},
+    render::{RenderSettings, render_scene},
+    spectrum::Emission,
+};
+use image::Rgb;
+
+/// Objects built from primitives with constructive solid geometry: a cube
+/// with a spherical bite, a rounded cube, a tube and a space station made of
+/// a hub, spokes and a ring.
+fn main() {
+    let frame = |position: [f64; 3]| ManifoldFrame::<CarthesianMinkowski> {
+        root: Coord {
+            components: FourVector([0.0, position[0], position[1], position[2]]),
+            _metric: std::marker::PhantomData,
+        },
+        axis: [
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+            FourVector([0.0, 1.0, 0.0, 0.0]),
+            FourVector([0.0, 0.0, 1.0, 0.0]),
+            FourVector([0.0, 0.0, 0.0, 1.0]),
+        ],
+    };
+    let cube = |position, half| BoxCollider {
+        frame: frame(position),
+        half_size: SpatialVec([half; 3]),
+        time_thickness: 1000.0,
+    };
+    let sphere = |position, radius| SphereCollider {
+        center: frame(position),
+        radius,
+        time_thickness: 1000.0,
+    };
+    let cylinder = |position, radius, half_height| CylinderCollider {
+        frame: frame(position),
+        radius,
+        half_height,
+        time_thickness: 1000.0,
+    };
+
+    let bitten = Csg::difference(
+        cube([-4.0, -4.0, 20.0], 1.5),
+        sphere([-5.0, -5.0, 18.5], 1.8),
+    );
+    let rounded = Csg::intersection(cube([4.0, -4.0, 20.0], 1.5), sphere([4.0, -4.0, 20.0], 1.9));
+    let tube = Csg::difference(
+        cylinder([-4.0, 4.0, 20.0], 1.5, 1.5),
+        cylinder([-4.0, 4.0, 20.0], 1.0, 2.0),
+    );
+    let station = Csg::union(
+        Csg::union(
+            sphere([4.0, 4.0, 20.0], 0.6),
+            Csg::union(
+                BoxCollider {
+                    frame: frame([4.0, 4.0, 20.0]),
+                    half_size: SpatialVec([2.0, 0.1, 0.1]),
+                    time_thickness: 1000.0,
+                },
+                BoxCollider {
+                    frame: frame([4.0, 4.0, 20.0]),
+                    half_size: SpatialVec([0.1, 2.0, 0.1]),
+                    time_thickness: 1000.0,
+                },
+            ),
+        ),
+        TorusCollider {
+            frame: frame([4.0, 4.0, 20.0]),
+            major_radius: 2.0,
+            minor_radius: 0.3,
+            time_thickness: 1000.0,
+        },
+    );
+
+    let objects: Vec<(Emission, Box<dyn RayIntersector<_>>)> = vec![
+        (Rgb([255, 80, 80]).into(), Box::new(bitten)),
+        (Rgb([80, 255, 80]).into(), Box::new(rounded)),
+        (Rgb([80, 160, 255]).into(), Box::new(tube)),
+        (Rgb([255, 200, 80]).into(), Box::new(station)),
+    ];
+
+    let image = render_scene(
+        &CarthesianMinkowski,
+        BasicCamera::new(frame([0.0, 0.0, 0.0]), 300, 300, 0.8),
+        objects,
+        BoundingBox {
+            bbox: [[-40.0, 1.0], [-20.0, 20.0], [-20.0, 20.0], [-1.0, 30.0]],
+            _metric: std::marker::PhantomData,
+        },
+        RenderSettings::new(0.5),
+    );
+    image.save("csg.png").unwrap();
+}
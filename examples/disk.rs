@@ -0,0 +1,50 @@
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame},
+    integrator::DormandPrince,
+    metric::KerrSchild,
+    objects::AccretionDisk,
+    render::{Beaming, RenderSettings, render_scene},
+};
+use image::Rgb;
+
+fn main() {
+    let metric = KerrSchild::new(1.0, 0.9);
+
+    // Camera slightly above the disk plane looking at the hole, with the spin
+    // axis pointing up in the image.
+    let camera_frame = ManifoldFrame {
+        root: Coord {
+            components: FourVector([0.0, -35.0, 0.0, 5.25]),
+            _metric: std::marker::PhantomData,
+        },
+        axis: [
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+            FourVector([0.0, 0.0, -1.0, 0.0]),
+            FourVector([0.0, -0.15, 0.0, -1.0]),
+            FourVector([0.0, 1.0, 0.0, -0.15]),
+        ],
+    }
+    .normalize(&metric);
+
+    let mut settings = RenderSettings::new(0.2).with_integrator(DormandPrince::new(1e-6));
+    settings.beaming = Beaming::Specific;
+
+    let image = render_scene(
+        &metric,
+        BasicCamera::new(camera_frame, 400, 200, 1.0),
+        vec![(
+            Rgb([0, 0, 0]).into(),
+            Box::new(AccretionDisk {
+                luminance: 0.02,
+                ..AccretionDisk::new(&metric, 20.0, 6000.0)
+            }),
+        )],
+        BoundingBox {
+            bbox: [[-300.0, 1.0], [-50.0, 50.0], [-50.0, 50.0], [-50.0, 50.0]],
+            _metric: std::marker::PhantomData,
+        },
+        settings,
+    );
+    image.save("disk.png").unwrap();
+}
@@ -0,0 +1,66 @@
+use std::f64::consts::PI;
+
+use grrender::{
+    geometry::{Coord, FourVector, ManifoldFrame, ManifoldVector, SpatialVec},
+    metric::{KerrSchild, Metric, Schwarzschild},
+    transport::{fermi_walker, parallel_transport},
+};
+
+/// Angle of the x axis of a transported frame from the x axis of the frame
+/// with the same four velocity lined up with the coordinate axes, turning
+/// towards axis 2 or 3 of the reference frame
+fn precession<T: Metric + ?Sized>(metric: &T, frame: ManifoldFrame<T>, towards: usize) -> f64 {
+    let reference = ManifoldFrame::from_four_velocity(metric, frame.root, frame.axis[0]);
+    let along = |i: usize| metric.inner(frame.root, frame.axis[1], reference.axis[i]);
+    along(towards).atan2(along(1))
+}
+
+/// Gyroscopes carried along by parallel and Fermi-Walker transport, precessing
+/// with respect to the distant stars
+fn main() {
+    // Geodetic precession over one circular orbit around a schwarzschild
+    // black hole, in the x-z plane, transporting along the known worldline
+    let metric = Schwarzschild::new(1.0);
+    let r: f64 = 15.0;
+    let omega = (metric.mass / r.powi(3)).sqrt();
+    let worldline = |t: f64| {
+        let (sin, cos) = (omega * t).sin_cos();
+        ManifoldVector {
+            root: Coord {
+                components: FourVector([t, r * sin, 0.0, -r * cos]),
+                _metric: std::marker::PhantomData,
+            },
+            components: FourVector([1.0, r * omega * cos, 0.0, r * omega * sin]),
+        }
+    };
+    let start = worldline(0.0);
+    let frame = ManifoldFrame::from_four_velocity(&metric, start.root, start.components);
+    let frame = parallel_transport(&metric, frame, worldline, 0.0, 2.0 * PI / omega, 2000);
+    println!(
+        "geodetic precession per orbit at r = {r}M: {:.5} rad, expected {:.5} rad",
+        precession(&metric, frame, 3),
+        2.0 * PI * (1.0 - (1.0 - 3.0 * metric.mass / r).sqrt()),
+    );
+
+    // Lense-Thirring precession of a gyroscope held static on the spin axis of
+    // a kerr black hole, accelerated against the pull of the hole
+    let metric = KerrSchild::new(1.0, 0.9);
+    let z: f64 = 20.0;
+    let root = Coord {
+        components: FourVector([0.0, 0.0, 0.0, z]),
+        _metric: std::marker::PhantomData,
+    };
+    let frame = ManifoldFrame::from_four_velocity(&metric, root, FourVector([1.0, 0.0, 0.0, 0.0]));
+    // Acceleration of the static worldline, a = Gamma(u, u), along the spin axis
+    let gamma = metric.christoffel(root);
+    let u = frame.axis[0];
+    let a = FourVector(std::array::from_fn(|mu| gamma[mu][0][0] * u.0[0] * u.0[0]));
+    let acceleration = SpatialVec([0.0, 0.0, metric.inner(root, a, frame.axis[3])]);
+    let duration = 2000.0;
+    let frame = fermi_walker(&metric, frame, |_| acceleration, 0.0, duration, 0.5);
+    println!(
+        "lense-thirring precession at z = {z}M over {duration}M: {:.4} rad, weak field {:.4} rad",
+        precession(&metric, frame, 2),
+        2.0 * metric.mass * metric.spin / z.powi(3) * duration,
+    );
+}
@@ -0,0 +1,62 @@
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame},
+    integrator::DormandPrince,
+    metric::KerrSchild,
+    objects::SphereCollider,
+    render::{RenderSettings, render_scene},
+};
+use image::Rgb;
+
+fn main() {
+    let metric = KerrSchild::new(1.0, 0.99);
+
+    // Camera in the equatorial plane looking along +x, so the spin axis
+    // points up in the image and the shadow shows the frame dragging.
+    let camera_frame = ManifoldFrame {
+        root: Coord {
+            components: FourVector([0.0, -30.0, 0.0, 0.0]),
+            _metric: std::marker::PhantomData,
+        },
+        axis: [
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+            FourVector([0.0, 0.0, 1.0, 0.0]),
+            FourVector([0.0, 0.0, 0.0, 1.0]),
+            FourVector([0.0, 1.0, 0.0, 0.0]),
+        ],
+    }
+    .normalize(&metric);
+
+    let backdrop = ManifoldFrame {
+        root: Coord {
+            components: FourVector([0.0, 25.0, 0.0, 0.0]),
+            _metric: std::marker::PhantomData,
+        },
+        axis: [
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+            FourVector([0.0, 1.0, 0.0, 0.0]),
+            FourVector([0.0, 0.0, 1.0, 0.0]),
+            FourVector([0.0, 0.0, 0.0, 1.0]),
+        ],
+    }
+    .normalize(&metric);
+
+    let image = render_scene(
+        &metric,
+        BasicCamera::new(camera_frame, 300, 300, 0.6),
+        vec![(
+            Rgb([255, 255, 255]).into(),
+            Box::new(SphereCollider {
+                center: backdrop,
+                radius: 12.0,
+                time_thickness: 1000.0,
+            }),
+        )],
+        BoundingBox {
+            bbox: [[-200.0, 1.0], [-40.0, 40.0], [-40.0, 40.0], [-40.0, 40.0]],
+            _metric: std::marker::PhantomData,
+        },
+        RenderSettings::new(0.2).with_integrator(DormandPrince::new(1e-6)),
+    );
+    image.save("kerr.png").unwrap();
+}
@@ -0,0 +1,88 @@
+use std::io::Cursor;
+
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame, SpatialVec},
+    metric::CarthesianMinkowski,
+    objects::{Mesh, RayIntersector, read_obj},
+    render::{RenderSettings, render_scene},
+    spectrum::Emission,
+};
+use image::Rgb;
+
+const CUBE: &str = "
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+f 1 2 3 4
+f 5 8 7 6
+f 1 5 6 2
+f 4 3 7 8
+f 1 4 8 5
+f 2 6 7 3
+";
+
+/// Cube passing the camera at 0.9c, with a different color on each face. The
+/// finite speed of light rotates it into view (terrell rotation), showing the
+/// face pointing away from its direction of motion. A mesh file to render
+/// instead can be given as argument.
+fn main() {
+    let origin = |position: [f64; 4]| Coord {
+        components: FourVector(position),
+        _metric: std::marker::PhantomData,
+    };
+    let at_rest = |position| {
+        ManifoldFrame::from_four_velocity(
+            &CarthesianMinkowski,
+            origin(position),
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+        )
+    };
+    let frame =
+        at_rest([-10.0, 0.0, 0.0, 10.0]).boosted(&CarthesianMinkowski, SpatialVec([0.9, 0.0, 0.0]));
+
+    let mut objects: Vec<(Emission, Box<dyn RayIntersector<_>>)> = vec![];
+    if let Some(path) = std::env::args().nth(1) {
+        objects.push((
+            Rgb([255, 255, 255]).into(),
+            Box::new(Mesh::open(path, frame, 1000.0).unwrap()),
+        ));
+    } else {
+        let (vertices, triangles) = read_obj(Cursor::new(CUBE)).unwrap();
+        let colors = [
+            Rgb([255, 80, 80]),
+            Rgb([80, 255, 80]),
+            Rgb([80, 80, 255]),
+            Rgb([255, 255, 80]),
+            Rgb([255, 80, 255]),
+            Rgb([80, 255, 255]),
+        ];
+        for (face, color) in triangles.chunks(2).zip(colors) {
+            objects.push((
+                color.into(),
+                Box::new(Mesh::new(frame, vertices.clone(), face.to_vec(), 1000.0).unwrap()),
+            ));
+        }
+    }
+
+    let mut settings = RenderSettings::new(0.5);
+    // Only show the geometry, the doppler shift would mostly hide the colors
+    settings.doppler = false;
+
+    let image = render_scene(
+        &CarthesianMinkowski,
+        BasicCamera::new(at_rest([0.0, 0.0, 0.0, 0.0]), 300, 300, 0.8),
+        objects,
+        BoundingBox {
+            bbox: [[-40.0, 1.0], [-20.0, 20.0], [-20.0, 20.0], [-1.0, 20.0]],
+            _metric: std::marker::PhantomData,
+        },
+        settings,
+    );
+    image.save("mesh.png").unwrap();
+}
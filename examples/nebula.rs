@@ -0,0 +1,54 @@
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame},
+    integrator::DormandPrince,
+    medium::FieldMedium,
+    metric::Schwarzschild,
+    render::{RenderSettings, render_scene},
+};
+
+fn main() {
+    let metric = Schwarzschild::new(1.0);
+
+    let camera_frame = ManifoldFrame {
+        root: Coord {
+            components: FourVector([0.0, 0.0, -6.0, -30.0]),
+            _metric: std::marker::PhantomData,
+        },
+        axis: [
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+            FourVector([0.0, 1.0, 0.0, 0.0]),
+            FourVector([0.0, 0.0, 1.0, -0.2]),
+            FourVector([0.0, 0.0, 0.2, 1.0]),
+        ],
+    }
+    .normalize(&metric);
+
+    // Glowing gas filling a torus around the hole, in the plane y = 0
+    let density = |x: FourVector| {
+        let [_, x, y, z] = x.0;
+        let ring = (x * x + z * z).sqrt() - 8.0;
+        (-(ring * ring + y * y) / 3.0).exp()
+    };
+    let torus = FieldMedium::new(
+        move |x| [0.12, 0.05, 0.015].map(|c| c * density(x)),
+        move |x| 0.15 * density(x),
+    );
+
+    let image = render_scene(
+        &metric,
+        BasicCamera::new(camera_frame, 200, 200, 1.0),
+        vec![],
+        BoundingBox {
+            bbox: [[-200.0, 1.0], [-40.0, 40.0], [-40.0, 40.0], [-40.0, 40.0]],
+            _metric: std::marker::PhantomData,
+        },
+        RenderSettings::new(0.2)
+            .with_integrator(DormandPrince {
+                max_step: 0.3,
+                ..DormandPrince::new(1e-6)
+            })
+            .with_medium(torus),
+    );
+    image.save("nebula.png").unwrap();
+}
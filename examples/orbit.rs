@@ -0,0 +1,68 @@
+use grrender::{
+    animation::{Observer, render_animation, save_gif, save_png_sequence},
+    background::SkyMap,
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame},
+    integrator::DormandPrince,
+    metric::Schwarzschild,
+    render::RenderSettings,
+};
+use image::{Rgb, RgbImage};
+
+/// Camera in free fall on a circular orbit around a schwarzschild black hole,
+/// rendered as an animated GIF, or as numbered PNGs with the given prefix.
+/// The camera frame is carried along by Fermi-Walker transport, like a
+/// gyroscope, so it keeps pointing the same way while the hole drifts out of
+/// view.
+fn main() {
+    let sky = SkyMap::new(&RgbImage::from_fn(720, 360, |x, y| {
+        if x % 30 == 0 || y % 30 == 0 {
+            Rgb([255, 255, 255])
+        } else if (x / 90 + y / 90) % 2 == 0 {
+            Rgb([40, 60, 120])
+        } else {
+            Rgb([120, 60, 40])
+        }
+    }))
+    .unwrap();
+
+    let metric = Schwarzschild::new(1.0);
+    let r: f64 = 15.0;
+    let omega = (metric.mass / r.powi(3)).sqrt();
+    let observer = Observer::new(
+        &metric,
+        ManifoldFrame {
+            root: Coord {
+                components: FourVector([0.0, 0.0, 0.0, -r]),
+                _metric: std::marker::PhantomData,
+            },
+            axis: [
+                FourVector([1.0, omega * r, 0.0, 0.0]),
+                FourVector([0.0, 1.0, 0.0, 0.0]),
+                FourVector([0.0, 0.0, 1.0, 0.0]),
+                FourVector([0.0, 0.0, 0.0, 1.0]),
+            ],
+        },
+    );
+
+    let frames = render_animation(
+        &metric,
+        observer,
+        4.0,
+        |frame| BasicCamera::new(frame, 160, 120, 1.6),
+        vec![],
+        BoundingBox {
+            bbox: [[-200.0, 100.0], [-60.0, 60.0], [-60.0, 60.0], [-60.0, 60.0]],
+            _metric: std::marker::PhantomData,
+        },
+        RenderSettings::new(0.2)
+            .with_integrator(DormandPrince::new(1e-6))
+            .with_background(sky),
+    )
+    .take(12);
+
+    match std::env::args().nth(1) {
+        Some(prefix) => save_png_sequence(frames, prefix).unwrap(),
+        None => save_gif(frames, "orbit.gif", 100).unwrap(),
+    }
+}
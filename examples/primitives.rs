@@ -0,0 +1,92 @@
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame, SpatialVec},
+    integrator::DormandPrince,
+    metric::Schwarzschild,
+    objects::{
+        BoxCollider, CylinderCollider, DiscCollider, PlaneCollider, RayIntersector, TorusCollider,
+    },
+    render::{RenderSettings, render_scene},
+    spectrum::Emission,
+};
+use image::Rgb;
+
+/// One of each primitive around a schwarzschild black hole, standing on a
+/// floor.
+fn main() {
+    let metric = Schwarzschild::new(1.0);
+
+    let static_frame = |position: [f64; 3]| {
+        ManifoldFrame {
+            root: Coord {
+                components: FourVector([0.0, position[0], position[1], position[2]]),
+                _metric: std::marker::PhantomData,
+            },
+            axis: [
+                FourVector([1.0, 0.0, 0.0, 0.0]),
+                FourVector([0.0, 1.0, 0.0, 0.0]),
+                FourVector([0.0, 0.0, 1.0, 0.0]),
+                FourVector([0.0, 0.0, 0.0, 1.0]),
+            ],
+        }
+        .normalize(&metric)
+    };
+
+    let objects: Vec<(Emission, Box<dyn RayIntersector<_>>)> = vec![
+        (
+            Rgb([120, 120, 120]).into(),
+            Box::new(PlaneCollider {
+                frame: static_frame([0.0, 6.0, 0.0]),
+                normal: SpatialVec([0.0, 1.0, 0.0]),
+                time_thickness: 1000.0,
+            }),
+        ),
+        (
+            Rgb([255, 80, 80]).into(),
+            Box::new(BoxCollider {
+                frame: static_frame([-8.0, 4.0, 10.0]),
+                half_size: SpatialVec([1.5, 2.0, 1.5]),
+                time_thickness: 1000.0,
+            }),
+        ),
+        (
+            Rgb([80, 255, 80]).into(),
+            Box::new(CylinderCollider {
+                frame: static_frame([8.0, 4.0, 10.0]),
+                radius: 1.5,
+                half_height: 2.0,
+                time_thickness: 1000.0,
+            }),
+        ),
+        (
+            Rgb([80, 160, 255]).into(),
+            Box::new(TorusCollider {
+                frame: static_frame([0.0, -4.0, 12.0]),
+                major_radius: 2.5,
+                minor_radius: 0.8,
+                time_thickness: 1000.0,
+            }),
+        ),
+        (
+            Rgb([255, 200, 80]).into(),
+            Box::new(DiscCollider {
+                frame: static_frame([0.0, 0.0, 20.0]),
+                inner_radius: 3.0,
+                outer_radius: 6.0,
+                time_thickness: 1000.0,
+            }),
+        ),
+    ];
+
+    let image = render_scene(
+        &metric,
+        BasicCamera::new(static_frame([0.0, 0.0, -20.0]), 300, 200, 1.4),
+        objects,
+        BoundingBox {
+            bbox: [[-200.0, 1.0], [-40.0, 40.0], [-30.0, 30.0], [-25.0, 40.0]],
+            _metric: std::marker::PhantomData,
+        },
+        RenderSettings::new(0.2).with_integrator(DormandPrince::new(1e-6)),
+    );
+    image.save("primitives.png").unwrap();
+}
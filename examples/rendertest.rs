@@ -1,20 +1,21 @@
 use std::f64::consts::PI;
 
 use grrender::{
-    camera::{BasicCamera, InstantCamera, InstantParallelRayCamera, ParallelRayCamera},
+    camera::ParallelRayCamera,
     geometry::{BoundingBox, Coord, FourVector, ManifoldFrame},
     metric::CarthesianMinkowski,
     objects::SphereCollider,
-    render::render_scene,
+    render::{RenderSettings, render_scene},
+    spectrum::Emission,
 };
-use image::Rgb;
 
 fn main() {
     let theta: f64 = 0.25 * PI;
-    let beta = 0.999;
+    let beta = 0.3;
     let gamma = 1.0 / (1.0f64 - beta * beta).sqrt();
 
-    let image = render_scene::<CarthesianMinkowski>(
+    let image = render_scene(
+        &CarthesianMinkowski,
         ParallelRayCamera::new(
             ManifoldFrame {
                 root: Coord {
@@ -33,7 +34,10 @@ fn main() {
             3.0,
         ),
         vec![(
-            Rgb([255, 255, 255]),
+            Emission::Blackbody {
+                temperature: 5800.0,
+                luminance: 1.0,
+            },
             Box::new(SphereCollider {
                 center: ManifoldFrame {
                     root: Coord {
@@ -55,7 +59,7 @@ fn main() {
             bbox: [[-7.0, 1.0], [-4.0, 4.0], [-4.0, 4.0], [-4.0, 4.0]],
             _metric: std::marker::PhantomData,
         },
-        0.01,
+        RenderSettings::new(0.01),
     );
     image.save("render.png").unwrap();
 }
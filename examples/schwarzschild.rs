@@ -0,0 +1,48 @@
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame},
+    integrator::DormandPrince,
+    metric::Schwarzschild,
+    objects::SphereCollider,
+    render::{RenderSettings, render_scene},
+};
+use image::Rgb;
+
+fn main() {
+    let metric = Schwarzschild::new(1.0);
+
+    let static_frame = |position: [f64; 3]| {
+        ManifoldFrame {
+            root: Coord {
+                components: FourVector([0.0, position[0], position[1], position[2]]),
+                _metric: std::marker::PhantomData,
+            },
+            axis: [
+                FourVector([1.0, 0.0, 0.0, 0.0]),
+                FourVector([0.0, 1.0, 0.0, 0.0]),
+                FourVector([0.0, 0.0, 1.0, 0.0]),
+                FourVector([0.0, 0.0, 0.0, 1.0]),
+            ],
+        }
+        .normalize(&metric)
+    };
+
+    let image = render_scene(
+        &metric,
+        BasicCamera::new(static_frame([0.0, 0.0, -30.0]), 300, 300, 0.8),
+        vec![(
+            Rgb([255, 255, 255]).into(),
+            Box::new(SphereCollider {
+                center: static_frame([0.0, 0.0, 15.0]),
+                radius: 3.0,
+                time_thickness: 1000.0,
+            }),
+        )],
+        BoundingBox {
+            bbox: [[-200.0, 1.0], [-40.0, 40.0], [-40.0, 40.0], [-40.0, 40.0]],
+            _metric: std::marker::PhantomData,
+        },
+        RenderSettings::new(0.2).with_integrator(DormandPrince::new(1e-6)),
+    );
+    image.save("schwarzschild.png").unwrap();
+}
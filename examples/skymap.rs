@@ -0,0 +1,56 @@
+use grrender::{
+    background::SkyMap,
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame},
+    integrator::DormandPrince,
+    metric::Schwarzschild,
+    render::{RenderSettings, render_scene},
+};
+use image::{Rgb, RgbImage};
+
+/// Lensing of an equirectangular sky map by a schwarzschild black hole. The
+/// sky map path can be given as argument, otherwise a grid is used.
+fn main() {
+    let sky = match std::env::args().nth(1) {
+        Some(path) => SkyMap::open(path).unwrap(),
+        None => SkyMap::new(&RgbImage::from_fn(720, 360, |x, y| {
+            if x % 30 == 0 || y % 30 == 0 {
+                Rgb([255, 255, 255])
+            } else if (x / 90 + y / 90) % 2 == 0 {
+                Rgb([40, 60, 120])
+            } else {
+                Rgb([120, 60, 40])
+            }
+        }))
+        .unwrap(),
+    };
+
+    let metric = Schwarzschild::new(1.0);
+    let camera_frame = ManifoldFrame {
+        root: Coord {
+            components: FourVector([0.0, 0.0, 0.0, -30.0]),
+            _metric: std::marker::PhantomData,
+        },
+        axis: [
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+            FourVector([0.0, 1.0, 0.0, 0.0]),
+            FourVector([0.0, 0.0, 1.0, 0.0]),
+            FourVector([0.0, 0.0, 0.0, 1.0]),
+        ],
+    }
+    .normalize(&metric);
+
+    let image = render_scene(
+        &metric,
+        BasicCamera::new(camera_frame, 300, 300, 1.0),
+        vec![],
+        BoundingBox {
+            bbox: [[-200.0, 1.0], [-60.0, 60.0], [-60.0, 60.0], [-60.0, 60.0]],
+            _metric: std::marker::PhantomData,
+        },
+        RenderSettings::new(0.2)
+            .with_integrator(DormandPrince::new(1e-6))
+            .with_background(sky),
+    );
+    image.save("skymap.png").unwrap();
+}
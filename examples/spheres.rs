@@ -0,0 +1,63 @@
+use grrender::{
+    camera::BasicCamera,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame},
+    integrator::DormandPrince,
+    metric::Schwarzschild,
+    objects::{RayIntersector, SphereCollider},
+    render::{RenderSettings, render_scene},
+    spectrum::Emission,
+};
+use image::Rgb;
+
+/// Wall of a thousand small spheres behind a schwarzschild black hole. Only
+/// the spheres near each ray step are tested, thanks to the broad phase.
+fn main() {
+    let metric = Schwarzschild::new(1.0);
+
+    let static_frame = |position: [f64; 3]| {
+        ManifoldFrame {
+            root: Coord {
+                components: FourVector([0.0, position[0], position[1], position[2]]),
+                _metric: std::marker::PhantomData,
+            },
+            axis: [
+                FourVector([1.0, 0.0, 0.0, 0.0]),
+                FourVector([0.0, 1.0, 0.0, 0.0]),
+                FourVector([0.0, 0.0, 1.0, 0.0]),
+                FourVector([0.0, 0.0, 0.0, 1.0]),
+            ],
+        }
+        .normalize(&metric)
+    };
+
+    let mut objects: Vec<(Emission, Box<dyn RayIntersector<_>>)> = vec![];
+    for i in 0..40 {
+        for j in 0..25 {
+            let color = if (i + j) % 2 == 0 {
+                Rgb([255, 200, 80])
+            } else {
+                Rgb([80, 160, 255])
+            };
+            objects.push((
+                color.into(),
+                Box::new(SphereCollider {
+                    center: static_frame([-39.0 + 2.0 * i as f64, -24.0 + 2.0 * j as f64, 20.0]),
+                    radius: 0.8,
+                    time_thickness: 1000.0,
+                }),
+            ));
+        }
+    }
+
+    let image = render_scene(
+        &metric,
+        BasicCamera::new(static_frame([0.0, 0.0, -30.0]), 400, 250, 1.6),
+        objects,
+        BoundingBox {
+            bbox: [[-200.0, 1.0], [-45.0, 45.0], [-30.0, 30.0], [-35.0, 25.0]],
+            _metric: std::marker::PhantomData,
+        },
+        RenderSettings::new(0.2).with_integrator(DormandPrince::new(1e-6)),
+    );
+    image.save("spheres.png").unwrap();
+}
@@ -0,0 +1,180 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use image::{
+    Delay, DynamicImage, Frame, ImageResult, RgbImage,
+    codecs::gif::{GifEncoder, Repeat},
+};
+
+use crate::{
+    broadphase::BroadPhase,
+    camera::Camera,
+    geometry::{BoundingBox, ManifoldFrame, SpatialVec},
+    metric::Metric,
+    objects::RayIntersector,
+    render::{RenderSettings, index_objects, render_indexed, to_ldr},
+    spectrum::Emission,
+    transport::fermi_walker,
+};
+
+/// Proper acceleration of an observer in its own rest frame, as a function of
+/// its proper time
+pub type Acceleration = Box<dyn Fn(f64) -> SpatialVec>;
+
+/// Observer following a worldline, carrying its rest frame along by
+/// Fermi-Walker transport: the frame does not rotate as seen by gyroscopes
+/// carried along.
+pub struct Observer<T: Metric + ?Sized> {
+    /// Current rest frame, whose time axis is the four velocity
+    pub frame: ManifoldFrame<T>,
+    pub proper_time: f64,
+    /// None for a free falling observer, following a timelike geodesic
+    pub acceleration: Option<Acceleration>,
+    /// Largest proper time step when advancing the observer
+    pub step: f64,
+}
+
+impl<T: Metric + ?Sized> Observer<T> {
+    /// Free falling observer with the given initial rest frame
+    pub fn new(metric: &T, frame: ManifoldFrame<T>) -> Self {
+        Observer {
+            frame: frame.normalize(metric),
+            proper_time: 0.0,
+            acceleration: None,
+            step: 0.05,
+        }
+    }
+
+    pub fn with_acceleration(self, acceleration: impl Fn(f64) -> SpatialVec + 'static) -> Self {
+        Observer {
+            acceleration: Some(Box::new(acceleration)),
+            ..self
+        }
+    }
+
+    /// Panics unless the step is positive and finite
+    pub fn with_step(self, step: f64) -> Self {
+        assert!(
+            step > 0.0 && step.is_finite(),
+            "observer step must be positive and finite, not {step}"
+        );
+        Observer { step, ..self }
+    }
+
+    /// Proper acceleration in the local frame at the given proper time
+    fn local_acceleration(&self, proper_time: f64) -> SpatialVec {
+        self.acceleration
+            .as_ref()
+            .map_or(SpatialVec::default(), |a| a(proper_time))
+    }
+
+    /// Move the observer along its worldline by the given proper time, with
+    /// fourth order Runge-Kutta steps of at most `step`
+    pub fn advance(&mut self, metric: &T, duration: f64) {
+        self.frame = fermi_walker(
+            metric,
+            self.frame,
+            |tau| self.local_acceleration(tau),
+            self.proper_time,
+            duration,
+            self.step,
+        );
+        self.proper_time += duration;
+    }
+}
+
+/// Endless sequence of images seen by an observer, one every `interval` of
+/// its proper time. Objects are indexed once for all images.
+pub struct Animation<'a, T: Metric + ?Sized, C, F: Fn(ManifoldFrame<T>) -> C> {
+    metric: &'a T,
+    observer: Observer<T>,
+    interval: f64,
+    camera: F,
+    objects: Vec<(Emission, Box<dyn RayIntersector<T>>)>,
+    index: BroadPhase<T>,
+    bounds: BoundingBox<T>,
+    settings: RenderSettings<T>,
+}
+
+impl<T: Metric + ?Sized, C: Camera<T>, F: Fn(ManifoldFrame<T>) -> C> Animation<'_, T, C, F> {
+    pub fn observer(&self) -> &Observer<T> {
+        &self.observer
+    }
+}
+
+impl<T: Metric + ?Sized, C: Camera<T>, F: Fn(ManifoldFrame<T>) -> C> Iterator
+    for Animation<'_, T, C, F>
+{
+    type Item = RgbImage;
+
+    fn next(&mut self) -> Option<RgbImage> {
+        let camera = (self.camera)(self.observer.frame);
+        let image = to_ldr(&render_indexed(
+            self.metric,
+            &camera,
+            &self.objects,
+            &self.index,
+            self.bounds,
+            &self.settings,
+        ));
+        self.observer.advance(self.metric, self.interval);
+        Some(image)
+    }
+}
+
+/// Render the scene as seen by an observer moving along its worldline, with
+/// cameras built from its rest frame by the camera function. Take as many
+/// images as needed from the returned iterator.
+pub fn render_animation<T: Metric + ?Sized, C: Camera<T>, F: Fn(ManifoldFrame<T>) -> C>(
+    metric: &T,
+    observer: Observer<T>,
+    interval: f64,
+    camera: F,
+    objects: Vec<(Emission, Box<dyn RayIntersector<T>>)>,
+    bounds: BoundingBox<T>,
+    settings: RenderSettings<T>,
+) -> Animation<'_, T, C, F> {
+    let index = index_objects(metric, &objects, bounds);
+    Animation {
+        metric,
+        observer,
+        interval,
+        camera,
+        objects,
+        index,
+        bounds,
+        settings,
+    }
+}
+
+/// Save images as numbered PNGs, named by the prefix followed by a four
+/// digit frame number, like `frames/0000.png`
+pub fn save_png_sequence(
+    frames: impl IntoIterator<Item = RgbImage>,
+    prefix: impl AsRef<Path>,
+) -> ImageResult<()> {
+    let prefix = prefix.as_ref().as_os_str().to_string_lossy();
+    for (i, frame) in frames.into_iter().enumerate() {
+        frame.save(format!("{prefix}{i:04}.png"))?;
+    }
+    Ok(())
+}
+
+/// Save images as a looping animated GIF, showing each for delay milliseconds
+pub fn save_gif(
+    frames: impl IntoIterator<Item = RgbImage>,
+    path: impl AsRef<Path>,
+    delay: u32,
+) -> ImageResult<()> {
+    let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+    encoder.set_repeat(Repeat::Infinite)?;
+    for frame in frames {
+        let rgba = DynamicImage::ImageRgb8(frame).into_rgba8();
+        encoder.encode_frame(Frame::from_parts(
+            rgba,
+            0,
+            0,
+            Delay::from_numer_denom_ms(delay, 1),
+        ))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use std::{f64::consts::PI, path::Path};
+
+use image::{ImageResult, Rgb32FImage, RgbImage};
+
+use crate::{color::from_rgb, geometry::SpatialVec, util::non_empty};
+
+/// What rays that escape the scene see, as a function of their direction on
+/// the celestial sphere.
+pub trait Background: Sync {
+    /// Radiance in linear sRGB arriving from the given unit direction, as
+    /// measured by a static observer far away
+    fn radiance(&self, direction: SpatialVec) -> [f64; 3];
+}
+
+/// The same color in every direction
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Uniform(pub [f64; 3]);
+
+impl Background for Uniform {
+    fn radiance(&self, _direction: SpatialVec) -> [f64; 3] {
+        self.0
+    }
+}
+
+/// Sky map in equirectangular projection.
+///
+/// The top row of the image is the pole in the -y direction, which is up for
+/// cameras whose frame axes line up with the coordinate axes. The horizontal
+/// center of the image is the +z direction, longitude grows towards +x.
+#[derive(Debug, Clone)]
+pub struct SkyMap {
+    /// Linear intensities
+    image: Rgb32FImage,
+}
+
+impl SkyMap {
+    /// Sky map from an 8 bit sRGB image, which must not be empty
+    pub fn new(image: &RgbImage) -> ImageResult<Self> {
+        non_empty(image.width(), image.height())?;
+        Ok(SkyMap {
+            image: Rgb32FImage::from_fn(image.width(), image.height(), |x, y| {
+                image::Rgb(from_rgb(*image.get_pixel(x, y)).map(|c| c as f32))
+            }),
+        })
+    }
+
+    /// Load an 8 bit sRGB sky map from disk
+    pub fn open(path: impl AsRef<Path>) -> ImageResult<Self> {
+        Self::new(&image::open(path)?.into_rgb8())
+    }
+
+    /// Linear intensities of the map
+    pub fn image(&self) -> &Rgb32FImage {
+        &self.image
+    }
+
+    fn texel(&self, x: i64, y: i64) -> [f64; 3] {
+        let (width, height) = (self.image.width() as i64, self.image.height() as i64);
+        let x = x.rem_euclid(width) as u32;
+        let y = y.clamp(0, height - 1) as u32;
+        self.image.get_pixel(x, y).0.map(|c| c as f64)
+    }
+}
+
+impl Background for SkyMap {
+    fn radiance(&self, direction: SpatialVec) -> [f64; 3] {
+        let [x, y, z] = direction.0;
+        let longitude = x.atan2(z);
+        let colatitude = (-y).clamp(-1.0, 1.0).acos();
+
+        // Bilinear interpolation between texel centers, wrapping in longitude
+        let u = (0.5 + longitude / (2.0 * PI)) * self.image.width() as f64 - 0.5;
+        let v = colatitude / PI * self.image.height() as f64 - 0.5;
+        let (u0, v0) = (u.floor(), v.floor());
+        let (fu, fv) = (u - u0, v - v0);
+        let (u0, v0) = (u0 as i64, v0 as i64);
+        let (a, b) = (self.texel(u0, v0), self.texel(u0 + 1, v0));
+        let (c, d) = (self.texel(u0, v0 + 1), self.texel(u0 + 1, v0 + 1));
+        std::array::from_fn(|i| {
+            (1.0 - fv) * ((1.0 - fu) * a[i] + fu * b[i]) + fv * ((1.0 - fu) * c[i] + fu * d[i])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_empty_map() {
+        assert!(SkyMap::new(&RgbImage::new(0, 0)).is_err());
+        assert!(SkyMap::new(&RgbImage::new(0, 4)).is_err());
+    }
+
+    #[test]
+    fn single_texel_map_is_uniform() {
+        let sky = SkyMap::new(&RgbImage::from_pixel(1, 1, image::Rgb([255, 255, 255]))).unwrap();
+        for direction in [[0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]] {
+            let radiance = sky.radiance(SpatialVec(direction));
+            assert!(
+                radiance.iter().all(|c| (c - 1.0).abs() < 1e-6),
+                "{radiance:?}"
+            );
+        }
+    }
+}
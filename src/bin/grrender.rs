@@ -0,0 +1,29 @@
+use std::process::ExitCode;
+
+use grrender::scene::Scene;
+
+/// Render a scene file, see the `scene` module for its format
+fn main() -> ExitCode {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: grrender <scene.toml>");
+        return ExitCode::from(2);
+    };
+    let scene = match Scene::open(&path) {
+        Ok(scene) => scene,
+        Err(e) => {
+            eprintln!("failed to load {path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let output = scene.output_path();
+    match scene.render().and_then(|image| image.save(&output)) {
+        Ok(()) => {
+            println!("saved {}", output.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("failed to render {path}: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
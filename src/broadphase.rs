@@ -0,0 +1,210 @@
+use std::marker::PhantomData;
+
+use crate::{
+    geometry::{BoundingBox, Coord},
+    metric::Metric,
+    objects::RayIntersector,
+};
+
+/// Tree of coordinate boxes subdividing the scene, listing which objects may
+/// be hit in each box, so that every ray step only has to be tested against
+/// the objects near it.
+///
+/// Boxes are split in half along their longest axis, time included, that
+/// separates some of their objects, until they hold at most `LEAF_SIZE`
+/// objects or no split separates them.
+/// Membership is decided by `RayIntersector::in_bounding_box`.
+#[derive(Debug)]
+pub struct BroadPhase<T: Metric + ?Sized> {
+    nodes: Vec<Node>,
+    _metric: PhantomData<T>,
+}
+
+#[derive(Debug)]
+struct Node {
+    bbox: [[f64; 2]; 4],
+    /// Indices of the child nodes, none for leaves
+    children: Option<[usize; 2]>,
+    /// Objects in the box, only kept for leaves
+    objects: Vec<usize>,
+}
+
+impl<T: Metric + ?Sized> BroadPhase<T> {
+    const LEAF_SIZE: usize = 4;
+    const MAX_DEPTH: usize = 24;
+
+    /// Index objects, identified by their position in the slice, within bounds
+    pub fn new(metric: &T, bounds: BoundingBox<T>, objects: &[&dyn RayIntersector<T>]) -> Self {
+        let mut index = BroadPhase {
+            nodes: vec![],
+            _metric: PhantomData,
+        };
+        let all = (0..objects.len())
+            .filter(|&i| objects[i].in_bounding_box(metric, bounds))
+            .collect();
+        index.build(metric, bounds.bbox, all, objects, 0);
+        index
+    }
+
+    fn build(
+        &mut self,
+        metric: &T,
+        bbox: [[f64; 2]; 4],
+        inside: Vec<usize>,
+        objects: &[&dyn RayIntersector<T>],
+        depth: usize,
+    ) -> usize {
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            bbox,
+            children: None,
+            objects: vec![],
+        });
+        if inside.len() <= Self::LEAF_SIZE || depth >= Self::MAX_DEPTH {
+            self.nodes[node].objects = inside;
+            return node;
+        }
+
+        // Split along the longest axis that separates some of the objects
+        let mut axes = [0, 1, 2, 3];
+        axes.sort_by(|&a, &b| (bbox[b][1] - bbox[b][0]).total_cmp(&(bbox[a][1] - bbox[a][0])));
+        let split = axes.into_iter().find_map(|axis| {
+            let middle = 0.5 * (bbox[axis][0] + bbox[axis][1]);
+            let halves = [0, 1].map(|half| {
+                let mut part = bbox;
+                part[axis][1 - half] = middle;
+                let members: Vec<usize> = inside
+                    .iter()
+                    .copied()
+                    .filter(|&i| {
+                        objects[i].in_bounding_box(
+                            metric,
+                            BoundingBox {
+                                bbox: part,
+                                _metric: PhantomData,
+                            },
+                        )
+                    })
+                    .collect();
+                (part, members)
+            });
+            let separates = halves
+                .iter()
+                .any(|(_, members)| members.len() < inside.len());
+            separates.then_some(halves)
+        });
+        let Some(halves) = split else {
+            self.nodes[node].objects = inside;
+            return node;
+        };
+
+        let [(low, low_members), (high, high_members)] = halves;
+        let children = [
+            self.build(metric, low, low_members, objects, depth + 1),
+            self.build(metric, high, high_members, objects, depth + 1),
+        ];
+        self.nodes[node].children = Some(children);
+        node
+    }
+
+    /// Objects that may be hit on the segment between two events, assuming the
+    /// ray stays within their coordinate box. Written into candidates in
+    /// increasing order, without duplicates.
+    pub fn candidates(&self, from: Coord<T>, to: Coord<T>, candidates: &mut Vec<usize>) {
+        candidates.clear();
+        let segment: [[f64; 2]; 4] = std::array::from_fn(|i| {
+            let (a, b) = (from.components.0[i], to.components.0[i]);
+            [a.min(b), a.max(b)]
+        });
+        self.collect(0, &segment, candidates);
+        candidates.sort_unstable();
+        candidates.dedup();
+    }
+
+    fn collect(&self, node: usize, segment: &[[f64; 2]; 4], candidates: &mut Vec<usize>) {
+        let node = &self.nodes[node];
+        let overlaps =
+            (0..4).all(|i| node.bbox[i][0] <= segment[i][1] && node.bbox[i][1] >= segment[i][0]);
+        if !overlaps {
+            return;
+        }
+        match node.children {
+            Some([low, high]) => {
+                self.collect(low, segment, candidates);
+                self.collect(high, segment, candidates);
+            }
+            None => candidates.extend(&node.objects),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::{FourVector, ManifoldFrame, ManifoldVector},
+        metric::{CarthesianMinkowski, testing::coord},
+        objects::SphereCollider,
+    };
+
+    /// Deterministic pseudo random numbers in [0, 1)
+    fn random(state: &mut u64) -> f64 {
+        *state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (*state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    #[test]
+    fn candidates_include_every_hit() {
+        let metric = CarthesianMinkowski;
+        let mut state = 7;
+        let spheres: Vec<SphereCollider<CarthesianMinkowski>> = (0..60)
+            .map(|_| {
+                let position = [0.0, 1.0, 2.0, 3.0].map(|_| 40.0 * random(&mut state) - 20.0);
+                SphereCollider {
+                    center: ManifoldFrame::from_four_velocity(
+                        &metric,
+                        coord([0.0, position[1], position[2], position[3]]),
+                        FourVector([1.0, 0.0, 0.0, 0.0]),
+                    ),
+                    radius: 0.5 + 2.0 * random(&mut state),
+                    time_thickness: 1e9,
+                }
+            })
+            .collect();
+        let objects: Vec<&dyn RayIntersector<CarthesianMinkowski>> = spheres
+            .iter()
+            .map(|s| s as &dyn RayIntersector<CarthesianMinkowski>)
+            .collect();
+        let bounds = BoundingBox {
+            bbox: [[-100.0, 100.0], [-25.0, 25.0], [-25.0, 25.0], [-25.0, 25.0]],
+            _metric: PhantomData,
+        };
+        let index = BroadPhase::new(&metric, bounds, &objects);
+
+        let (mut hits, mut candidates) = (0, vec![]);
+        for _ in 0..300 {
+            // Light rays traced backwards from random points
+            let direction = [0.0; 3].map(|_| 2.0 * random(&mut state) - 1.0);
+            let mut ray = CarthesianMinkowski::lightray(crate::geometry::SpatialVec(direction));
+            ray.root = coord([0.0, 1.0, 2.0, 3.0].map(|_| 40.0 * random(&mut state) - 20.0));
+            ray.root.components.0[0] = 0.0;
+            let step = 0.5;
+            while bounds.contains(ray.root) {
+                let next: ManifoldVector<CarthesianMinkowski> = metric.step_geodesic(ray, step);
+                index.candidates(ray.root, next.root, &mut candidates);
+                for (i, object) in objects.iter().enumerate() {
+                    if object.intersects(&metric, ray, step).is_some() {
+                        hits += 1;
+                        assert!(candidates.contains(&i), "missed object {i}");
+                    }
+                }
+                ray = next;
+            }
+        }
+        // Enough hits to mean something, on an index that did split
+        assert!(hits > 50, "{hits} hits");
+        assert!(index.nodes.len() > 1);
+    }
+}
@@ -3,10 +3,13 @@ use crate::{
     metric::{CarthesianMinkowski, Metric},
 };
 
-pub trait Camera<T: Metric + ?Sized> {
+/// Source of the initial rays.
+pub trait Camera<T: Metric + ?Sized>: Sync {
     fn screen_size(&self) -> (usize, usize);
+    /// Rest frame of the observer, used for frequency shifts
+    fn frame(&self) -> ManifoldFrame<T>;
     /// Which direction the ray should go. [0,0,1] corresponds to the "main" camera direction
-    fn ray(&self, pixel: (usize, usize)) -> ManifoldVector<T>;
+    fn ray(&self, metric: &T, pixel: (usize, usize)) -> ManifoldVector<T>;
 }
 
 pub struct BasicCamera<T: Metric + ?Sized> {
@@ -34,12 +37,16 @@ impl<T: Metric + ?Sized> Camera<T> for BasicCamera<T> {
         (self.pix_width + 1, self.pix_height + 1)
     }
 
-    fn ray(&self, pixel: (usize, usize)) -> ManifoldVector<T> {
+    fn frame(&self) -> ManifoldFrame<T> {
+        self.frame
+    }
+
+    fn ray(&self, metric: &T, pixel: (usize, usize)) -> ManifoldVector<T> {
         let x: f64 = (pixel.0 as f64) / (self.pix_width as f64) - 0.5;
         let y: f64 = (pixel.1 as f64) / (self.pix_height as f64) - 0.5;
 
         let dir = SpatialVec([x * self.plane_width, y * self.plane_height, 1.0]);
-        T::from_local(self.frame, CarthesianMinkowski::lightray(dir))
+        metric.from_local(self.frame, CarthesianMinkowski::lightray(dir))
     }
 }
 
@@ -68,12 +75,16 @@ impl<T: Metric + ?Sized> Camera<T> for InstantCamera<T> {
         (self.pix_width + 1, self.pix_height + 1)
     }
 
-    fn ray(&self, pixel: (usize, usize)) -> ManifoldVector<T> {
+    fn frame(&self) -> ManifoldFrame<T> {
+        self.frame
+    }
+
+    fn ray(&self, metric: &T, pixel: (usize, usize)) -> ManifoldVector<T> {
         let x: f64 = (pixel.0 as f64) / (self.pix_width as f64) - 0.5;
         let y: f64 = (pixel.1 as f64) / (self.pix_height as f64) - 0.5;
 
         let dir = SpatialVec([x * self.plane_width, y * self.plane_height, 1.0]);
-        T::from_local(self.frame, CarthesianMinkowski::instantray(dir))
+        metric.from_local(self.frame, CarthesianMinkowski::instantray(dir))
     }
 }
 
@@ -102,11 +113,15 @@ impl<T: Metric + ?Sized> Camera<T> for ParallelRayCamera<T> {
         (self.pix_width + 1, self.pix_height + 1)
     }
 
-    fn ray(&self, pixel: (usize, usize)) -> ManifoldVector<T> {
+    fn frame(&self) -> ManifoldFrame<T> {
+        self.frame
+    }
+
+    fn ray(&self, metric: &T, pixel: (usize, usize)) -> ManifoldVector<T> {
         let x: f64 = (pixel.0 as f64) / (self.pix_width as f64) - 0.5;
         let y: f64 = (pixel.1 as f64) / (self.pix_height as f64) - 0.5;
 
-        T::from_local(
+        metric.from_local(
             self.frame,
             ManifoldVector {
                 root: Coord {
@@ -144,11 +159,15 @@ impl<T: Metric + ?Sized> Camera<T> for InstantParallelRayCamera<T> {
         (self.pix_width + 1, self.pix_height + 1)
     }
 
-    fn ray(&self, pixel: (usize, usize)) -> ManifoldVector<T> {
+    fn frame(&self) -> ManifoldFrame<T> {
+        self.frame
+    }
+
+    fn ray(&self, metric: &T, pixel: (usize, usize)) -> ManifoldVector<T> {
         let x: f64 = (pixel.0 as f64) / (self.pix_width as f64) - 0.5;
         let y: f64 = (pixel.1 as f64) / (self.pix_height as f64) - 0.5;
 
-        T::from_local(
+        metric.from_local(
             self.frame,
             ManifoldVector {
                 root: Coord {
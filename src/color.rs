@@ -0,0 +1,77 @@
+use image::Rgb;
+
+use crate::util::sqr;
+
+/// Representative wavelengths (nm) of the red, green and blue channels
+const CHANNEL_WAVELENGTHS: [f64; 3] = [610.0, 550.0, 465.0];
+/// Width (nm) of the spectral band each channel is taken to cover
+const CHANNEL_WIDTH: f64 = 35.0;
+
+/// Response of each channel to emission in each channel's band after its
+/// frequency has been multiplied by g.
+fn band_response(g: f64) -> [[f64; 3]; 3] {
+    std::array::from_fn(|observed| {
+        std::array::from_fn(|emitted| {
+            let shifted = CHANNEL_WAVELENGTHS[emitted] / g;
+            (-sqr(CHANNEL_WAVELENGTHS[observed] - shifted) / (4.0 * sqr(CHANNEL_WIDTH))).exp()
+        })
+    })
+}
+
+fn invert3(m: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let cofactor = |i: usize, j: usize| {
+        let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+        let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let det = (0..3).map(|j| m[0][j] * cofactor(0, j)).sum::<f64>();
+    std::array::from_fn(|i| std::array::from_fn(|j| cofactor(j, i) / det))
+}
+
+/// Shift the color of emitted light by frequency ratio g = nu_obs / nu_emit.
+///
+/// Each channel is treated as a narrow spectral band, which is moved along
+/// the spectrum and projected back onto the channels. A ratio of 1 leaves the
+/// color unchanged, light shifted out of the visible range fades to black.
+/// Only the hue is affected, intensity changes are left to the caller.
+pub fn frequency_shift(color: [f64; 3], g: f64) -> [f64; 3] {
+    let shifted = band_response(g);
+    let unshifted = invert3(band_response(1.0));
+    let emitted: [f64; 3] =
+        std::array::from_fn(|i| (0..3).map(|j| unshifted[i][j] * color[j]).sum::<f64>());
+    std::array::from_fn(|i| {
+        (0..3)
+            .map(|j| shifted[i][j] * emitted[j])
+            .sum::<f64>()
+            .max(0.0)
+    })
+}
+
+/// Decode an sRGB encoded channel value in the range 0..1 to linear intensity
+fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a linear intensity in the range 0..1 with the sRGB transfer function
+fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Convert an 8 bit sRGB color to linear intensities in the range 0..1
+pub fn from_rgb(color: Rgb<u8>) -> [f64; 3] {
+    color.0.map(|c| srgb_to_linear(c as f64 / 255.0))
+}
+
+/// Convert linear intensities to an 8 bit sRGB color, clipping out of range
+/// values
+pub fn to_rgb(color: [f64; 3]) -> Rgb<u8> {
+    Rgb(color.map(|c| (linear_to_srgb(c.clamp(0.0, 1.0)) * 255.0).round() as u8))
+}
@@ -5,10 +5,17 @@ use std::{
     ops::{Add, AddAssign, Mul, Sub, SubAssign},
 };
 
-use crate::metric::Metric;
+#[cfg(feature = "serde")]
+use crate::metric::MetricName;
+use crate::{
+    lorentz::LorentzTransform,
+    metric::Metric,
+    util::{cross3, dot3, normalize3},
+};
 
 /// Basic 4-vector with math operations
 #[derive(Copy, Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FourVector(pub [f64; 4]);
 
 impl Mul<FourVector> for f64 {
@@ -66,10 +73,7 @@ pub struct Coord<T: Metric + ?Sized> {
 
 impl<T: Metric + ?Sized> Clone for Coord<T> {
     fn clone(&self) -> Self {
-        Self {
-            components: self.components.clone(),
-            _metric: PhantomData,
-        }
+        *self
     }
 }
 
@@ -77,6 +81,11 @@ impl<T: Metric + ?Sized> Copy for Coord<T> {}
 
 /// Vector in the tangent space of a manifold
 #[derive(PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "T: MetricName")
+)]
 pub struct ManifoldVector<T: Metric + ?Sized> {
     pub root: Coord<T>,
     pub components: FourVector,
@@ -84,25 +93,27 @@ pub struct ManifoldVector<T: Metric + ?Sized> {
 
 impl<T: Metric + ?Sized> Clone for ManifoldVector<T> {
     fn clone(&self) -> Self {
-        Self {
-            root: self.root,
-            components: self.components,
-        }
+        *self
     }
 }
 
 impl<T: Metric + ?Sized> Copy for ManifoldVector<T> {}
 
 #[derive(PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "T: MetricName")
+)]
 pub struct ManifoldFrame<T: Metric + ?Sized> {
     pub root: Coord<T>,
     pub axis: [FourVector; 4],
 }
 
 impl<T: Metric + ?Sized> ManifoldFrame<T> {
-    pub fn normal(self) -> bool {
+    pub fn normal(self, metric: &T) -> bool {
         const EPS: f64 = 1e-10;
-        if (T::norm(ManifoldVector {
+        if (metric.norm(ManifoldVector {
             root: self.root,
             components: self.axis[0],
         }) + 1.0)
@@ -112,7 +123,7 @@ impl<T: Metric + ?Sized> ManifoldFrame<T> {
             return false;
         }
         for i in 1..4 {
-            if (T::norm(ManifoldVector {
+            if (metric.norm(ManifoldVector {
                 root: self.root,
                 components: self.axis[i],
             }) - 1.0)
@@ -124,7 +135,7 @@ impl<T: Metric + ?Sized> ManifoldFrame<T> {
         }
         for i in 0..4 {
             for j in i + 1..4 {
-                if T::inner(self.root, self.axis[i], self.axis[j]).abs() > EPS {
+                if metric.inner(self.root, self.axis[i], self.axis[j]).abs() > EPS {
                     return false;
                 }
             }
@@ -132,65 +143,156 @@ impl<T: Metric + ?Sized> ManifoldFrame<T> {
         true
     }
 
-    pub fn normalize(self) -> Self {
+    pub fn normalize(self, metric: &T) -> Self {
         let t = (1.0
-            / T::norm(ManifoldVector {
-                root: self.root,
-                components: self.axis[0],
-            })
-            .abs()
-            .sqrt())
+            / metric
+                .norm(ManifoldVector {
+                    root: self.root,
+                    components: self.axis[0],
+                })
+                .abs()
+                .sqrt())
             * self.axis[0];
-        let x = self.axis[1] + T::inner(self.root, t, self.axis[1]) * t;
+        let x = self.axis[1] + metric.inner(self.root, t, self.axis[1]) * t;
         let x = (1.0
-            / T::norm(ManifoldVector {
-                root: self.root,
-                components: x,
-            })
-            .abs()
-            .sqrt())
+            / metric
+                .norm(ManifoldVector {
+                    root: self.root,
+                    components: x,
+                })
+                .abs()
+                .sqrt())
             * x;
-        let y = self.axis[2] + T::inner(self.root, t, self.axis[2]) * t
-            - T::inner(self.root, x, self.axis[2]) * x;
+        let y = self.axis[2] + metric.inner(self.root, t, self.axis[2]) * t
+            - metric.inner(self.root, x, self.axis[2]) * x;
         let y = (1.0
-            / T::norm(ManifoldVector {
-                root: self.root,
-                components: y,
-            })
-            .abs()
-            .sqrt())
+            / metric
+                .norm(ManifoldVector {
+                    root: self.root,
+                    components: y,
+                })
+                .abs()
+                .sqrt())
             * y;
-        let z = self.axis[3] + T::inner(self.root, t, self.axis[3]) * t
-            - T::inner(self.root, x, self.axis[3]) * x
-            - T::inner(self.root, y, self.axis[3]) * y;
+        let z = self.axis[3] + metric.inner(self.root, t, self.axis[3]) * t
+            - metric.inner(self.root, x, self.axis[3]) * x
+            - metric.inner(self.root, y, self.axis[3]) * y;
         let z = (1.0
-            / T::norm(ManifoldVector {
-                root: self.root,
-                components: z,
-            })
-            .abs()
-            .sqrt())
+            / metric
+                .norm(ManifoldVector {
+                    root: self.root,
+                    components: z,
+                })
+                .abs()
+                .sqrt())
             * z;
         ManifoldFrame {
             root: self.root,
             axis: [t, x, y, z],
         }
     }
+
+    /// Frame of an observer at root moving with four velocity u, such as
+    /// `(1, 0, 0, 0)` for a static observer. The spatial axes follow the
+    /// coordinate axes as closely as possible, x first.
+    pub fn from_four_velocity(metric: &T, root: Coord<T>, u: FourVector) -> Self {
+        ManifoldFrame {
+            root,
+            axis: [
+                u,
+                FourVector([0.0, 1.0, 0.0, 0.0]),
+                FourVector([0.0, 0.0, 1.0, 0.0]),
+                FourVector([0.0, 0.0, 0.0, 1.0]),
+            ],
+        }
+        .normalize(metric)
+    }
+
+    /// Vector with the given components along the axes of the frame
+    fn combine(&self, local: [f64; 4]) -> FourVector {
+        (0..4).map(|i| local[i] * self.axis[i]).sum()
+    }
+
+    /// Frame whose axes have the components given by the columns of the
+    /// transform along the axes of this frame. Transforms compose, so
+    /// `frame.transformed(a).transformed(b) == frame.transformed(a * b)`.
+    pub fn transformed(self, transform: LorentzTransform) -> Self {
+        ManifoldFrame {
+            root: self.root,
+            axis: array::from_fn(|a| self.combine(transform.0.map(|row| row[a]))),
+        }
+    }
+
+    /// Frame of an observer moving with velocity, in units of c along the
+    /// spatial axes of this frame, related to it by a pure boost
+    pub fn boosted(self, metric: &T, velocity: SpatialVec) -> Self {
+        self.transformed(LorentzTransform::boost(velocity))
+            .normalize(metric)
+    }
+
+    /// Frame rotated by angle, in radians, counterclockwise about an axis
+    /// given along the spatial axes of this frame
+    pub fn rotated(self, metric: &T, axis: SpatialVec, angle: f64) -> Self {
+        self.transformed(LorentzTransform::rotation(axis, angle))
+            .normalize(metric)
+    }
+
+    /// Frame turned so that its z axis, the viewing direction of cameras,
+    /// points at target, keeping its y axis as close as possible. The
+    /// direction is taken in the first order local coordinates of the frame,
+    /// ignoring light travel time and lensing.
+    pub fn look_at(self, metric: &T, target: Coord<T>) -> Self {
+        let local = metric
+            .into_local(
+                self,
+                ManifoldVector {
+                    root: target,
+                    components: FourVector::default(),
+                },
+            )
+            .root
+            .components
+            .0;
+        let z = normalize3([local[1], local[2], local[3]]);
+        let x = cross3([0.0, 1.0, 0.0], z);
+        let x = if dot3(x, x) > 1e-12 {
+            normalize3(x)
+        } else {
+            // Looking along y, keep x instead
+            normalize3(cross3(cross3(z, [1.0, 0.0, 0.0]), z))
+        };
+        let y = cross3(z, x);
+        self.with_spatial_axes(metric, [x, y, z])
+    }
+
+    /// Frame with the same time axis and the given spatial axes, in components
+    /// along the spatial axes of this frame
+    fn with_spatial_axes(self, metric: &T, axes: [[f64; 3]; 3]) -> Self {
+        let spatial = |e: [f64; 3]| self.combine([0.0, e[0], e[1], e[2]]);
+        ManifoldFrame {
+            root: self.root,
+            axis: [
+                self.axis[0],
+                spatial(axes[0]),
+                spatial(axes[1]),
+                spatial(axes[2]),
+            ],
+        }
+        .normalize(metric)
+    }
 }
 
 impl<T: Metric + ?Sized> Clone for ManifoldFrame<T> {
     fn clone(&self) -> Self {
-        Self {
-            root: self.root,
-            axis: self.axis,
-        }
+        *self
     }
 }
 
 impl<T: Metric + ?Sized> Copy for ManifoldFrame<T> {}
 
 /// Vector describing relative distances in flat 3-dimensional euclidean space
-#[derive(PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpatialVec(pub [f64; 3]);
 
 #[derive(Debug, PartialEq)]
@@ -201,10 +303,7 @@ pub struct BoundingBox<T: Metric + ?Sized> {
 
 impl<T: Metric + ?Sized> Clone for BoundingBox<T> {
     fn clone(&self) -> Self {
-        Self {
-            bbox: self.bbox.clone(),
-            _metric: PhantomData,
-        }
+        *self
     }
 }
 
@@ -212,8 +311,149 @@ impl<T: Metric + ?Sized> Copy for BoundingBox<T> {}
 
 impl<T: Metric + ?Sized> BoundingBox<T> {
     pub fn contains(self, point: Coord<T>) -> bool {
-        (0..4).into_iter().all(|i| {
+        (0..4).all(|i| {
             self.bbox[i][0] <= point.components.0[i] && self.bbox[i][1] >= point.components.0[i]
         })
     }
 }
+
+/// Coordinates and bounding boxes are serialized with the name of their
+/// metric, which is checked when deserializing.
+#[cfg(feature = "serde")]
+mod tagged {
+    use std::{borrow::Cow, marker::PhantomData};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    use super::{BoundingBox, Coord, FourVector};
+    use crate::metric::{Metric, MetricName};
+
+    #[derive(Serialize, Deserialize)]
+    struct Tagged<V> {
+        metric: Cow<'static, str>,
+        #[serde(flatten)]
+        value: V,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct CoordFields {
+        components: FourVector,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct BoxFields {
+        bbox: [[f64; 2]; 4],
+    }
+
+    fn untag<'de, T: MetricName + ?Sized, V: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<V, D::Error> {
+        let tagged = Tagged::<V>::deserialize(deserializer)?;
+        if tagged.metric != T::NAME {
+            return Err(D::Error::custom(format!(
+                "expected {} metric, found {}",
+                T::NAME,
+                tagged.metric
+            )));
+        }
+        Ok(tagged.value)
+    }
+
+    impl<T: Metric + MetricName + ?Sized> Serialize for Coord<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Tagged {
+                metric: Cow::Borrowed(T::NAME),
+                value: CoordFields {
+                    components: self.components,
+                },
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, T: Metric + MetricName + ?Sized> Deserialize<'de> for Coord<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let CoordFields { components } = untag::<T, _, _>(deserializer)?;
+            Ok(Coord {
+                components,
+                _metric: PhantomData,
+            })
+        }
+    }
+
+    impl<T: Metric + MetricName + ?Sized> Serialize for BoundingBox<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Tagged {
+                metric: Cow::Borrowed(T::NAME),
+                value: BoxFields { bbox: self.bbox },
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, T: Metric + MetricName + ?Sized> Deserialize<'de> for BoundingBox<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let BoxFields { bbox } = untag::<T, _, _>(deserializer)?;
+            Ok(BoundingBox {
+                bbox,
+                _metric: PhantomData,
+            })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::metric::{Kerr, Schwarzschild, testing::coord};
+
+    #[test]
+    fn toml_round_trip() {
+        let metric = Schwarzschild::new(1.0);
+        let root: Coord<Schwarzschild> = coord([1.0, 2.0, -3.0, 4.5]);
+        let text = toml::to_string(&root).unwrap();
+        assert_eq!(toml::from_str::<Coord<Schwarzschild>>(&text).unwrap(), root);
+
+        let frame =
+            ManifoldFrame::from_four_velocity(&metric, root, FourVector([1.2, 0.1, 0.0, 0.3]));
+        let text = toml::to_string(&frame).unwrap();
+        assert_eq!(
+            toml::from_str::<ManifoldFrame<Schwarzschild>>(&text).unwrap(),
+            frame
+        );
+
+        let bbox: BoundingBox<Schwarzschild> = BoundingBox {
+            bbox: [[-1.0, 1.0], [-2.0, 2.0], [-3.0, 3.0], [-4.0, 4.0]],
+            _metric: PhantomData,
+        };
+        let text = toml::to_string(&bbox).unwrap();
+        assert_eq!(
+            toml::from_str::<BoundingBox<Schwarzschild>>(&text).unwrap(),
+            bbox
+        );
+    }
+
+    #[test]
+    fn rejects_other_metric() {
+        let metric = Schwarzschild::new(1.0);
+        let root: Coord<Schwarzschild> = coord([0.0, 5.0, 0.0, 0.0]);
+        let text = toml::to_string(&root).unwrap();
+        let error = toml::from_str::<Coord<Kerr>>(&text).unwrap_err();
+        assert!(
+            error.to_string().contains("expected kerr metric"),
+            "{error}"
+        );
+
+        let frame =
+            ManifoldFrame::from_four_velocity(&metric, root, FourVector([1.0, 0.0, 0.0, 0.0]));
+        let text = toml::to_string(&frame).unwrap();
+        assert!(toml::from_str::<ManifoldFrame<Kerr>>(&text).is_err());
+
+        let bbox: BoundingBox<Schwarzschild> = BoundingBox {
+            bbox: [[-1.0, 1.0]; 4],
+            _metric: PhantomData,
+        };
+        let text = toml::to_string(&bbox).unwrap();
+        assert!(toml::from_str::<BoundingBox<Kerr>>(&text).is_err());
+    }
+}
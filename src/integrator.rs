@@ -0,0 +1,383 @@
+use std::marker::PhantomData;
+
+use crate::{
+    geometry::{Coord, FourVector, ManifoldVector},
+    metric::Metric,
+};
+
+/// Result of a single integration step
+#[derive(Debug)]
+pub struct Step<T: Metric + ?Sized> {
+    /// Ray at the end of the step
+    pub ray: ManifoldVector<T>,
+    /// Affine parameter distance actually covered
+    pub taken: f64,
+    /// Suggested size for the next step
+    pub next: f64,
+}
+
+/// Numerical scheme for following a ray along its geodesic.
+pub trait Integrator<T: Metric + ?Sized>: Sync {
+    /// Advance the ray, attempting a step of the given size. Adaptive
+    /// integrators may take a smaller step than requested.
+    fn step(&self, metric: &T, ray: ManifoldVector<T>, step: f64) -> Step<T>;
+}
+
+fn vector<T: Metric + ?Sized>(x: FourVector, v: FourVector) -> ManifoldVector<T> {
+    ManifoldVector {
+        root: Coord {
+            components: x,
+            _metric: PhantomData,
+        },
+        components: v,
+    }
+}
+
+/// Use the metric's own `Metric::step_geodesic`, with a fixed step size.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Native;
+
+impl<T: Metric + ?Sized> Integrator<T> for Native {
+    fn step(&self, metric: &T, ray: ManifoldVector<T>, step: f64) -> Step<T> {
+        Step {
+            ray: metric.step_geodesic(ray, step),
+            taken: step,
+            next: step,
+        }
+    }
+}
+
+/// First order explicit euler method, with a fixed step size.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Euler;
+
+impl<T: Metric + ?Sized> Integrator<T> for Euler {
+    fn step(&self, metric: &T, ray: ManifoldVector<T>, step: f64) -> Step<T> {
+        let a = metric.geodesic_acceleration(ray);
+        Step {
+            ray: vector(
+                ray.root.components + step * ray.components,
+                ray.components + step * a,
+            ),
+            taken: step,
+            next: step,
+        }
+    }
+}
+
+/// Classic fourth order runge-kutta method, with a fixed step size.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RungeKutta4;
+
+impl<T: Metric + ?Sized> Integrator<T> for RungeKutta4 {
+    fn step(&self, metric: &T, ray: ManifoldVector<T>, step: f64) -> Step<T> {
+        let x0 = ray.root.components;
+        let v0 = ray.components;
+
+        let a1 = metric.geodesic_acceleration(ray);
+        let x2 = x0 + (0.5 * step) * v0;
+        let v2 = v0 + (0.5 * step) * a1;
+        let a2 = metric.geodesic_acceleration(vector(x2, v2));
+        let x3 = x0 + (0.5 * step) * v2;
+        let v3 = v0 + (0.5 * step) * a2;
+        let a3 = metric.geodesic_acceleration(vector(x3, v3));
+        let x4 = x0 + step * v3;
+        let v4 = v0 + step * a3;
+        let a4 = metric.geodesic_acceleration(vector(x4, v4));
+
+        Step {
+            ray: vector(
+                x0 + (step / 6.0) * (v0 + 2.0 * v2 + 2.0 * v3 + v4),
+                v0 + (step / 6.0) * (a1 + 2.0 * a2 + 2.0 * a3 + a4),
+            ),
+            taken: step,
+            next: step,
+        }
+    }
+}
+
+/// Adaptive dormand-prince 5(4) runge-kutta method.
+///
+/// The step size is chosen such that the estimated local error on each
+/// component of position and direction stays below
+/// `abs_tolerance + rel_tolerance * |component|`. This shrinks the steps in
+/// strongly curved regions, such as near a horizon, and grows them far away.
+/// Steps stay within `min_step` and `max_step`, but are never longer than
+/// requested.
+///
+/// Stepping panics unless `0 < min_step <= max_step`.
+#[derive(Debug, Clone, Copy)]
+pub struct DormandPrince {
+    pub abs_tolerance: f64,
+    pub rel_tolerance: f64,
+    pub min_step: f64,
+    pub max_step: f64,
+}
+
+impl DormandPrince {
+    const A: [[f64; 6]; 7] = [
+        [0.0; 6],
+        [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+        [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+        [
+            19372.0 / 6561.0,
+            -25360.0 / 2187.0,
+            64448.0 / 6561.0,
+            -212.0 / 729.0,
+            0.0,
+            0.0,
+        ],
+        [
+            9017.0 / 3168.0,
+            -355.0 / 33.0,
+            46732.0 / 5247.0,
+            49.0 / 176.0,
+            -5103.0 / 18656.0,
+            0.0,
+        ],
+        [
+            35.0 / 384.0,
+            0.0,
+            500.0 / 1113.0,
+            125.0 / 192.0,
+            -2187.0 / 6784.0,
+            11.0 / 84.0,
+        ],
+    ];
+    /// Fifth order solution weights
+    const B: [f64; 7] = [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+        0.0,
+    ];
+    /// Fourth order solution weights, for the error estimate
+    const B_STAR: [f64; 7] = [
+        5179.0 / 57600.0,
+        0.0,
+        7571.0 / 16695.0,
+        393.0 / 640.0,
+        -92097.0 / 339200.0,
+        187.0 / 2100.0,
+        1.0 / 40.0,
+    ];
+
+    pub fn new(tolerance: f64) -> Self {
+        DormandPrince {
+            abs_tolerance: tolerance,
+            rel_tolerance: tolerance,
+            min_step: 1e-6,
+            max_step: 1.0,
+        }
+    }
+
+    /// Single trial step, returning the new position and direction together
+    /// with the normalized error estimate.
+    fn attempt<T: Metric + ?Sized>(
+        &self,
+        metric: &T,
+        ray: ManifoldVector<T>,
+        step: f64,
+    ) -> (FourVector, FourVector, f64) {
+        let x0 = ray.root.components;
+        let v0 = ray.components;
+
+        let mut kx = [FourVector::default(); 7];
+        let mut kv = [FourVector::default(); 7];
+        for stage in 0..7 {
+            let x = x0
+                + step
+                    * (0..stage)
+                        .map(|j| Self::A[stage][j] * kx[j])
+                        .sum::<FourVector>();
+            let v = v0
+                + step
+                    * (0..stage)
+                        .map(|j| Self::A[stage][j] * kv[j])
+                        .sum::<FourVector>();
+            kx[stage] = v;
+            kv[stage] = metric.geodesic_acceleration(vector(x, v));
+        }
+
+        let x = x0 + step * (0..7).map(|j| Self::B[j] * kx[j]).sum::<FourVector>();
+        let v = v0 + step * (0..7).map(|j| Self::B[j] * kv[j]).sum::<FourVector>();
+        let ex = step
+            * (0..7)
+                .map(|j| (Self::B[j] - Self::B_STAR[j]) * kx[j])
+                .sum::<FourVector>();
+        let ev = step
+            * (0..7)
+                .map(|j| (Self::B[j] - Self::B_STAR[j]) * kv[j])
+                .sum::<FourVector>();
+
+        let mut error: f64 = 0.0;
+        for i in 0..4 {
+            let scale_x = self.abs_tolerance + self.rel_tolerance * x0.0[i].abs().max(x.0[i].abs());
+            let scale_v = self.abs_tolerance + self.rel_tolerance * v0.0[i].abs().max(v.0[i].abs());
+            error = error.max(ex.0[i].abs() / scale_x);
+            error = error.max(ev.0[i].abs() / scale_v);
+        }
+        // A step that runs into a singularity is always too large
+        if [x, v, ex, ev]
+            .iter()
+            .any(|c| !c.0.iter().all(|v| v.is_finite()))
+        {
+            error = f64::INFINITY;
+        }
+        (x, v, error)
+    }
+}
+
+impl<T: Metric + ?Sized> Integrator<T> for DormandPrince {
+    fn step(&self, metric: &T, ray: ManifoldVector<T>, step: f64) -> Step<T> {
+        assert!(
+            0.0 < self.min_step && self.min_step <= self.max_step,
+            "step bounds must satisfy 0 < min_step <= max_step, not {} and {}",
+            self.min_step,
+            self.max_step
+        );
+        // Never step further than requested, even below the minimum
+        let min_step = self.min_step.min(step);
+        let mut step = step.clamp(min_step, self.max_step);
+        loop {
+            let (x, v, error) = self.attempt(metric, ray, step);
+
+            let accept = error <= 1.0 || step <= min_step;
+            let factor = (0.9 * error.powf(-0.2)).clamp(0.2, 5.0);
+
+            if accept {
+                return Step {
+                    ray: vector(x, v),
+                    taken: step,
+                    next: (step * factor).clamp(self.min_step, self.max_step),
+                };
+            }
+            step = (step * factor).max(min_step);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::ManifoldFrame,
+        metric::{Schwarzschild, testing::coord},
+    };
+
+    /// Light ray from a static observer at the given position, heading along
+    /// the given spatial direction
+    fn ray(
+        metric: &Schwarzschild,
+        root: [f64; 4],
+        direction: [f64; 3],
+    ) -> ManifoldVector<Schwarzschild> {
+        let frame = ManifoldFrame::from_four_velocity(
+            metric,
+            coord(root),
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+        );
+        ManifoldVector {
+            root: frame.root,
+            components: frame.axis[0] + (0..3).map(|i| direction[i] * frame.axis[i + 1]).sum(),
+        }
+    }
+
+    #[test]
+    fn dormand_prince_adapts_to_curvature() {
+        let metric = Schwarzschild::new(1.0);
+        let integrator = DormandPrince {
+            max_step: 100.0,
+            ..DormandPrince::new(1e-8)
+        };
+        let near = integrator.step(
+            &metric,
+            ray(&metric, [0.0, 2.2, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            1.0,
+        );
+        assert!(near.taken < 0.1, "took {} near the horizon", near.taken);
+        assert!(near.next < 1.0);
+
+        let far = integrator.step(
+            &metric,
+            ray(&metric, [0.0, 1000.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            1.0,
+        );
+        assert_eq!(far.taken, 1.0);
+        assert!(far.next > 1.0, "next {} far away", far.next);
+    }
+
+    #[test]
+    fn dormand_prince_never_steps_further_than_requested() {
+        let metric = Schwarzschild::new(1.0);
+        let integrator = DormandPrince::new(1e-6);
+        for (root, requested) in [
+            ([0.0, 2.2, 0.0, 0.0], 1.0),
+            ([0.0, 20.0, 0.0, 0.0], 0.5),
+            ([0.0, 1000.0, 0.0, 0.0], 50.0),
+            ([0.0, 20.0, 0.0, 0.0], 1e-8),
+        ] {
+            let step = integrator.step(&metric, ray(&metric, root, [0.0, 1.0, 0.0]), requested);
+            assert!(
+                step.taken <= requested,
+                "took {} of {requested}",
+                step.taken
+            );
+            assert!(step.taken > 0.0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "min_step <= max_step")]
+    fn dormand_prince_rejects_inverted_bounds() {
+        let metric = Schwarzschild::new(1.0);
+        let integrator = DormandPrince {
+            min_step: 2.0,
+            max_step: 1.0,
+            ..DormandPrince::new(1e-6)
+        };
+        integrator.step(
+            &metric,
+            ray(&metric, [0.0, 20.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            1.0,
+        );
+    }
+
+    /// Distance from a circular orbit at r = 10M after a quarter orbit
+    fn circular_orbit_error(integrator: impl Integrator<Schwarzschild>) -> f64 {
+        let metric = Schwarzschild::new(1.0);
+        let r: f64 = 10.0;
+        let omega = (1.0 / r.powi(3)).sqrt();
+        let ut = 1.0 / (1.0 - 3.0 / r).sqrt();
+        let mut orbit = ManifoldVector {
+            root: coord([0.0, 0.0, 0.0, -r]),
+            components: FourVector([ut, ut * r * omega, 0.0, 0.0]),
+        };
+        let steps = 200;
+        let tau = 0.5 * std::f64::consts::PI / omega / ut;
+        for _ in 0..steps {
+            orbit = integrator.step(&metric, orbit, tau / steps as f64).ray;
+        }
+        let t = ut * tau;
+        let expected = [t, r * (omega * t).sin(), 0.0, -r * (omega * t).cos()];
+        (0..4)
+            .map(|i| (orbit.root.components.0[i] - expected[i]).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    #[test]
+    fn runge_kutta_beats_euler() {
+        let euler = circular_orbit_error(Euler);
+        let rk4 = circular_orbit_error(RungeKutta4);
+        assert!(rk4 < 1e-6, "runge-kutta off by {rk4}");
+        assert!(
+            rk4 < 1e-3 * euler,
+            "runge-kutta off by {rk4}, euler by {euler}"
+        );
+    }
+}
@@ -1,6 +1,18 @@
+pub mod animation;
+pub mod background;
+pub mod broadphase;
 pub mod camera;
+pub mod color;
 pub mod geometry;
+pub mod integrator;
+pub mod lorentz;
+pub mod medium;
 pub mod metric;
 pub mod objects;
 pub mod render;
+#[cfg(feature = "scene")]
+pub mod scene;
+pub mod spectrum;
+pub mod texture;
+pub mod transport;
 mod util;
@@ -0,0 +1,243 @@
+use std::{array, ops::Mul};
+
+use crate::{
+    geometry::{FourVector, SpatialVec},
+    util::{cross3, dot3, normalize3},
+};
+
+/// Lorentz transformation of components in an orthonormal frame, as a matrix
+/// `m[mu][nu]` acting on column vectors: `v'^mu = m[mu][nu] v^nu`.
+///
+/// Transforms compose by multiplication, `(a * b) * v == a * (b * v)`, and
+/// re-orient frames through `ManifoldFrame::transformed`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LorentzTransform(pub [[f64; 4]; 4]);
+
+impl Default for LorentzTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl LorentzTransform {
+    pub const IDENTITY: Self = LorentzTransform([
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+
+    /// Boost to velocity v, in units of c: maps the time axis to the four
+    /// velocity `gamma (1, v)`
+    pub fn boost(velocity: SpatialVec) -> Self {
+        let v = velocity.0;
+        let v2 = dot3(v, v);
+        if v2 == 0.0 {
+            return Self::IDENTITY;
+        }
+        let gamma = 1.0 / (1.0 - v2).sqrt();
+        LorentzTransform(array::from_fn(|mu| {
+            array::from_fn(|nu| match (mu, nu) {
+                (0, 0) => gamma,
+                (0, i) | (i, 0) => gamma * v[i - 1],
+                (i, j) => (i == j) as u8 as f64 + (gamma - 1.0) * v[i - 1] * v[j - 1] / v2,
+            })
+        }))
+    }
+
+    /// Boost along direction by rapidity, which adds up for collinear boosts
+    pub fn boost_rapidity(direction: SpatialVec, rapidity: f64) -> Self {
+        Self::boost(SpatialVec(
+            normalize3(direction.0).map(|c| c * rapidity.tanh()),
+        ))
+    }
+
+    /// Rotation by angle, in radians, counterclockwise about axis
+    pub fn rotation(axis: SpatialVec, angle: f64) -> Self {
+        let k = normalize3(axis.0);
+        let (sin, cos) = angle.sin_cos();
+        // Rodrigues' rotation of each unit vector gives the columns
+        let columns = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]].map(|e| {
+            let cross = cross3(k, e);
+            let along = dot3(k, e) * (1.0 - cos);
+            array::from_fn::<f64, 3, _>(|i| e[i] * cos + cross[i] * sin + k[i] * along)
+        });
+        LorentzTransform(array::from_fn(|mu| {
+            array::from_fn(|nu| match (mu, nu) {
+                (0, 0) => 1.0,
+                (0, _) | (_, 0) => 0.0,
+                (i, j) => columns[j - 1][i - 1],
+            })
+        }))
+    }
+
+    /// Inverse transform, `eta m^T eta`
+    pub fn inverse(&self) -> Self {
+        let sign = |mu: usize| if mu == 0 { -1.0 } else { 1.0 };
+        LorentzTransform(array::from_fn(|mu| {
+            array::from_fn(|nu| sign(mu) * sign(nu) * self.0[nu][mu])
+        }))
+    }
+
+    /// Velocity of the transformed time axis
+    pub fn velocity(&self) -> SpatialVec {
+        let u = self.0.map(|row| row[0]);
+        SpatialVec([u[1] / u[0], u[2] / u[0], u[3] / u[0]])
+    }
+
+    /// Split into a boost and a rotation, `self == boost(velocity) * rotation`.
+    /// For the composition of two non collinear boosts, the rotation is their
+    /// Thomas-Wigner rotation.
+    pub fn decompose(&self) -> (SpatialVec, LorentzTransform) {
+        let velocity = self.velocity();
+        (velocity, Self::boost(velocity).inverse() * *self)
+    }
+
+    /// Axis and counterclockwise angle, in radians, of the rotation part
+    pub fn axis_angle(&self) -> (SpatialVec, f64) {
+        let r = self.decompose().1.0;
+        let trace = r[1][1] + r[2][2] + r[3][3];
+        let angle = ((trace - 1.0) / 2.0).clamp(-1.0, 1.0).acos();
+        let axis = [r[3][2] - r[2][3], r[1][3] - r[3][1], r[2][1] - r[1][2]];
+        if dot3(axis, axis) > 1e-20 {
+            return (SpatialVec(normalize3(axis)), angle);
+        }
+        if angle < 1.0 {
+            // No rotation, any axis will do
+            return (SpatialVec([0.0, 0.0, 1.0]), 0.0);
+        }
+        // Half turn, the matrix is symmetric with r = 2 k k^T - 1
+        let i = (1..4).max_by(|&a, &b| r[a][a].total_cmp(&r[b][b])).unwrap();
+        let column: [f64; 3] = array::from_fn(|j| r[j + 1][i] + (j + 1 == i) as u8 as f64);
+        (SpatialVec(normalize3(column)), angle)
+    }
+}
+
+impl Mul<LorentzTransform> for LorentzTransform {
+    type Output = LorentzTransform;
+
+    fn mul(self, rhs: LorentzTransform) -> Self::Output {
+        LorentzTransform(array::from_fn(|mu| {
+            array::from_fn(|nu| (0..4).map(|k| self.0[mu][k] * rhs.0[k][nu]).sum())
+        }))
+    }
+}
+
+impl Mul<FourVector> for LorentzTransform {
+    type Output = FourVector;
+
+    fn mul(self, rhs: FourVector) -> Self::Output {
+        FourVector(array::from_fn(|mu| {
+            (0..4).map(|nu| self.0[mu][nu] * rhs.0[nu]).sum()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use super::*;
+    use crate::{
+        geometry::Coord,
+        metric::{CarthesianMinkowski, Metric},
+    };
+
+    const EPS: f64 = 1e-12;
+
+    fn vectors() -> [FourVector; 4] {
+        [
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+            FourVector([2.0, 0.3, -0.5, 1.1]),
+            FourVector([1.0, 1.0, 0.0, 0.0]),
+            FourVector([-0.4, 0.7, 2.0, -1.3]),
+        ]
+    }
+
+    fn transforms() -> Vec<LorentzTransform> {
+        let boost = LorentzTransform::boost(SpatialVec([0.3, -0.5, 0.6]));
+        let rotation = LorentzTransform::rotation(SpatialVec([1.0, 2.0, -0.5]), 0.9);
+        vec![
+            boost,
+            rotation,
+            LorentzTransform::boost_rapidity(SpatialVec([0.0, 0.0, 1.0]), 2.5),
+            boost * rotation,
+            rotation * boost * LorentzTransform::boost(SpatialVec([-0.7, 0.1, 0.0])),
+        ]
+    }
+
+    fn assert_close(a: LorentzTransform, b: LorentzTransform, eps: f64) {
+        for mu in 0..4 {
+            for nu in 0..4 {
+                assert!(
+                    (a.0[mu][nu] - b.0[mu][nu]).abs() < eps,
+                    "{a:?} differs from {b:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inner_product_is_invariant() {
+        let metric = CarthesianMinkowski;
+        let root = Coord {
+            components: FourVector::default(),
+            _metric: PhantomData,
+        };
+        for m in transforms() {
+            for a in vectors() {
+                for b in vectors() {
+                    let before = metric.inner(root, a, b);
+                    let after = metric.inner(root, m * a, m * b);
+                    assert!((before - after).abs() < 1e-9, "{before} != {after}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_transform() {
+        for m in transforms() {
+            assert_close(m.inverse() * m, LorentzTransform::IDENTITY, 1e-9);
+            assert_close(m * m.inverse(), LorentzTransform::IDENTITY, 1e-9);
+        }
+    }
+
+    #[test]
+    fn collinear_rapidities_add() {
+        let direction = SpatialVec([1.0, -2.0, 0.5]);
+        let composed = LorentzTransform::boost_rapidity(direction, 0.4)
+            * LorentzTransform::boost_rapidity(direction, 1.1);
+        assert_close(
+            composed,
+            LorentzTransform::boost_rapidity(direction, 1.5),
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn boost_velocity_round_trips() {
+        let v = SpatialVec([0.2, -0.4, 0.5]);
+        let velocity = LorentzTransform::boost(v).velocity();
+        for i in 0..3 {
+            assert!((velocity.0[i] - v.0[i]).abs() < EPS);
+        }
+    }
+
+    #[test]
+    fn wigner_rotation_of_perpendicular_boosts() {
+        let (v1, v2) = (0.6, 0.8);
+        let m = LorentzTransform::boost(SpatialVec([0.0, v2, 0.0]))
+            * LorentzTransform::boost(SpatialVec([v1, 0.0, 0.0]));
+        let (velocity, rotation) = m.decompose();
+        assert_close(LorentzTransform::boost(velocity) * rotation, m, 1e-9);
+
+        let (axis, angle) = m.axis_angle();
+        let g1 = 1.0 / (1.0 - v1 * v1).sqrt();
+        let g2 = 1.0 / (1.0 - v2 * v2).sqrt();
+        let expected = ((g1 + g2) / (1.0 + g1 * g2)).acos();
+        assert!((angle - expected).abs() < 1e-9, "{angle} != {expected}");
+        // The rotation is in the plane of the two boosts
+        assert!((axis.0[2].abs() - 1.0).abs() < 1e-9, "{axis:?}");
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{
+    geometry::{Coord, FourVector, ManifoldVector},
+    metric::Metric,
+};
+
+/// Participating medium, which emits and absorbs light along every ray passing
+/// through it, such as thick tori, jets or nebulae.
+pub trait Medium<T: Metric + ?Sized>: Sync {
+    /// Emitted radiance per unit length in linear sRGB, measured in the rest
+    /// frame of the medium at root
+    fn emissivity(&self, metric: &T, root: Coord<T>) -> [f64; 3];
+
+    /// Fraction of light absorbed per unit length, measured in the rest frame
+    /// of the medium at root. Absorption is taken to be the same for all
+    /// colors.
+    fn absorption(&self, metric: &T, root: Coord<T>) -> f64;
+
+    /// Four velocity of the medium at root, if it can be at rest there.
+    /// Defaults to static observers.
+    fn velocity(&self, metric: &T, root: Coord<T>) -> Option<FourVector> {
+        static_velocity(metric, root)
+    }
+}
+
+/// Four velocity of the observer at rest in the coordinates at root. There is
+/// none where the time coordinate is not timelike, such as inside an
+/// ergosphere.
+pub fn static_velocity<T: Metric + ?Sized>(metric: &T, root: Coord<T>) -> Option<FourVector> {
+    let time = FourVector([1.0, 0.0, 0.0, 0.0]);
+    let norm = metric.norm(ManifoldVector {
+        root,
+        components: time,
+    });
+    (norm < 0.0).then(|| (1.0 / (-norm).sqrt()) * time)
+}
+
+/// Medium at rest in the coordinates, given by closures computing emissivity
+/// and absorption from the coordinate components of a point
+#[derive(Clone, Copy)]
+pub struct FieldMedium<E, A>
+where
+    E: Fn(FourVector) -> [f64; 3] + Sync,
+    A: Fn(FourVector) -> f64 + Sync,
+{
+    pub emissivity: E,
+    pub absorption: A,
+}
+
+impl<E, A> FieldMedium<E, A>
+where
+    E: Fn(FourVector) -> [f64; 3] + Sync,
+    A: Fn(FourVector) -> f64 + Sync,
+{
+    pub fn new(emissivity: E, absorption: A) -> Self {
+        FieldMedium {
+            emissivity,
+            absorption,
+        }
+    }
+}
+
+impl<T, E, A> Medium<T> for FieldMedium<E, A>
+where
+    T: Metric + ?Sized,
+    E: Fn(FourVector) -> [f64; 3] + Sync,
+    A: Fn(FourVector) -> f64 + Sync,
+{
+    fn emissivity(&self, _metric: &T, root: Coord<T>) -> [f64; 3] {
+        (self.emissivity)(root.components)
+    }
+
+    fn absorption(&self, _metric: &T, root: Coord<T>) -> f64 {
+        (self.absorption)(root.components)
+    }
+}
@@ -1,15 +1,34 @@
 use std::{array, marker::PhantomData};
 
-use crate::geometry::{Coord, FourVector, ManifoldFrame, ManifoldVector, SpatialVec};
+use crate::{
+    geometry::{Coord, FourVector, ManifoldFrame, ManifoldVector, SpatialVec},
+    integrator::{Integrator, RungeKutta4},
+};
 
-pub trait Metric: std::fmt::Debug {
-    fn step_geodesic(start: ManifoldVector<Self>, step: f64) -> ManifoldVector<Self>;
+mod kerr;
+mod numeric;
+mod schwarzschild;
 
-    fn norm(vector: ManifoldVector<Self>) -> f64;
+pub use kerr::{Kerr, KerrSchild};
+pub use numeric::NumericMetric;
+pub use schwarzschild::Schwarzschild;
 
-    fn inner(root: Coord<Self>, a: FourVector, b: FourVector) -> f64;
+/// A spacetime metric. Implementors may carry parameters (mass, spin, charge,
+/// ...), so every operation goes through a metric instance.
+pub trait Metric: std::fmt::Debug + Sync {
+    /// Follow the geodesic through start for the given affine parameter step.
+    /// Defaults to a fourth order runge-kutta step on the geodesic equation.
+    fn step_geodesic(&self, start: ManifoldVector<Self>, step: f64) -> ManifoldVector<Self> {
+        RungeKutta4.step(self, start, step).ray
+    }
+
+    fn norm(&self, vector: ManifoldVector<Self>) -> f64;
+
+    fn inner(&self, root: Coord<Self>, a: FourVector, b: FourVector) -> f64;
 
+    #[allow(clippy::wrong_self_convention)]
     fn into_local(
+        &self,
         root: ManifoldFrame<Self>,
         secondary: ManifoldVector<Self>,
     ) -> ManifoldVector<CarthesianMinkowski> {
@@ -18,34 +37,152 @@ pub trait Metric: std::fmt::Debug {
         ManifoldVector {
             root: Coord {
                 components: FourVector(array::from_fn(|i| {
-                    Self::inner(root.root, root.axis[i], posdelta)
-                        / Self::norm(ManifoldVector {
-                            root: root.root,
-                            components: root.axis[i],
-                        })
-                        .abs()
-                        .sqrt()
+                    self.inner(root.root, root.axis[i], posdelta)
+                        / self
+                            .norm(ManifoldVector {
+                                root: root.root,
+                                components: root.axis[i],
+                            })
+                            .abs()
+                            .sqrt()
                         * (if i == 0 { -1. } else { 1. })
                 })),
                 _metric: PhantomData,
             },
             components: FourVector(array::from_fn(|i| {
-                Self::inner(root.root, root.axis[i], secondary.components)
-                    / Self::norm(ManifoldVector {
-                        root: root.root,
-                        components: root.axis[i],
-                    })
-                    .abs()
-                    .sqrt()
+                self.inner(root.root, root.axis[i], secondary.components)
+                    / self
+                        .norm(ManifoldVector {
+                            root: root.root,
+                            components: root.axis[i],
+                        })
+                        .abs()
+                        .sqrt()
                     * (if i == 0 { -1. } else { 1. })
             })),
         }
     }
 
+    /// Map a vector given in the local minkowski coordinates of frame into the
+    /// tangent space of the manifold. For curved metrics this is a first order
+    /// approximation around the frame root.
+    #[allow(clippy::wrong_self_convention)]
     fn from_local(
+        &self,
         frame: ManifoldFrame<Self>,
         vector: ManifoldVector<CarthesianMinkowski>,
-    ) -> ManifoldVector<Self>;
+    ) -> ManifoldVector<Self> {
+        ManifoldVector {
+            root: Coord {
+                components: frame.root.components
+                    + (0..4)
+                        .map(|i| vector.root.components.0[i] * frame.axis[i])
+                        .sum(),
+                _metric: PhantomData,
+            },
+            components: (0..4).map(|i| vector.components.0[i] * frame.axis[i]).sum(),
+        }
+    }
+
+    /// Whether the vector has fallen behind a horizon, after which it can never
+    /// reach an observer outside again
+    fn captured(&self, _vector: ManifoldVector<Self>) -> bool {
+        false
+    }
+
+    /// Unit direction on the celestial sphere a ray is heading to once it has
+    /// left the scene. Defaults to the spatial coordinate components, which is
+    /// right for metrics in asymptotically cartesian coordinates.
+    fn escape_direction(&self, vector: ManifoldVector<Self>) -> SpatialVec {
+        let [_, x, y, z] = vector.components.0;
+        let length = (x * x + y * y + z * z).sqrt();
+        SpatialVec([x / length, y / length, z / length])
+    }
+
+    /// Christoffel symbols of the second kind at root, indexed as `[mu][alpha][beta]`
+    fn christoffel(&self, root: Coord<Self>) -> Christoffel;
+
+    /// Coordinate acceleration of a geodesic with the given tangent vector
+    fn geodesic_acceleration(&self, vector: ManifoldVector<Self>) -> FourVector {
+        let gamma = self.christoffel(vector.root);
+        let v = vector.components.0;
+        FourVector(array::from_fn(|mu| {
+            -(0..4)
+                .flat_map(|a| (0..4).map(move |b| (a, b)))
+                .map(|(a, b)| gamma[mu][a][b] * v[a] * v[b])
+                .sum::<f64>()
+        }))
+    }
+}
+
+/// Stationary, axisymmetric spacetime around a central mass, with an
+/// equatorial plane that supports circular orbits.
+pub trait Axisymmetric: Metric {
+    fn mass(&self) -> f64;
+
+    /// Angular momentum per unit mass `a = J/M`, positive for rotation towards
+    /// increasing azimuth
+    fn spin(&self) -> f64 {
+        0.0
+    }
+
+    /// Boyer-lindquist radius, azimuth and height above the equatorial plane
+    /// of root
+    fn equatorial(&self, root: Coord<Self>) -> [f64; 3];
+
+    /// Killing vector generating rotations about the axis, `d/dphi`, at root
+    fn rotation(&self, root: Coord<Self>) -> FourVector;
+
+    /// Radius of the innermost stable circular orbit, for orbits rotating
+    /// with the hole
+    fn isco(&self) -> f64 {
+        isco_radius(self.mass(), self.spin(), 1.0)
+    }
+
+    /// Radius of the innermost stable circular orbit, for orbits rotating
+    /// against the hole
+    fn retrograde_isco(&self) -> f64 {
+        isco_radius(self.mass(), self.spin(), -1.0)
+    }
+}
+
+/// Innermost stable circular orbit of bardeen, press and teukolsky, with
+/// direction 1 for prograde and -1 for retrograde orbits
+fn isco_radius(mass: f64, spin: f64, direction: f64) -> f64 {
+    let a = spin.abs() / mass;
+    let z1 = 1.0 + (1.0 - a * a).cbrt() * ((1.0 + a).cbrt() + (1.0 - a).cbrt());
+    let z2 = (3.0 * a * a + z1 * z1).sqrt();
+    mass * (3.0 + z2 - direction * ((3.0 - z1) * (3.0 + z1 + 2.0 * z2)).sqrt())
+}
+
+/// Name identifying a spacetime. Serialized coordinates are tagged with it,
+/// so that they cannot be loaded for another spacetime.
+pub trait MetricName {
+    const NAME: &'static str;
+}
+
+pub type Christoffel = [[[f64; 4]; 4]; 4];
+
+/// Compute christoffel symbols from the inverse metric and the partial
+/// derivatives of the metric, where `dg[k][mu][nu]` is the derivative of
+/// `g_{mu nu}` with respect to coordinate `k`.
+pub fn christoffel_from_derivatives(ginv: [[f64; 4]; 4], dg: [[[f64; 4]; 4]; 4]) -> Christoffel {
+    array::from_fn(|mu| {
+        array::from_fn(|a| {
+            array::from_fn(|b| {
+                0.5 * (0..4)
+                    .map(|nu| ginv[mu][nu] * (dg[a][nu][b] + dg[b][nu][a] - dg[nu][a][b]))
+                    .sum::<f64>()
+            })
+        })
+    })
+}
+
+/// Contract a (0,2) tensor with two vectors
+pub(crate) fn contract(g: [[f64; 4]; 4], a: FourVector, b: FourVector) -> f64 {
+    (0..4)
+        .map(|mu| (0..4).map(|nu| g[mu][nu] * a.0[mu] * b.0[nu]).sum::<f64>())
+        .sum()
 }
 
 #[derive(Debug)]
@@ -56,7 +193,7 @@ impl CarthesianMinkowski {
 
     pub fn lightray(spatial: SpatialVec) -> ManifoldVector<Self> {
         let raw = FourVector([
-            -spatial.0.into_iter().map(|v| v.powi(2)).sum::<f64>(),
+            -spatial.0.iter().map(|v| v.powi(2)).sum::<f64>().sqrt(),
             spatial.0[0],
             spatial.0[1],
             spatial.0[2],
@@ -83,8 +220,12 @@ impl CarthesianMinkowski {
     }
 }
 
+impl MetricName for CarthesianMinkowski {
+    const NAME: &'static str = "minkowski";
+}
+
 impl Metric for CarthesianMinkowski {
-    fn step_geodesic(start: ManifoldVector<Self>, step: f64) -> ManifoldVector<Self> {
+    fn step_geodesic(&self, start: ManifoldVector<Self>, step: f64) -> ManifoldVector<Self> {
         ManifoldVector {
             root: Coord {
                 components: start.root.components + step * start.components,
@@ -94,37 +235,98 @@ impl Metric for CarthesianMinkowski {
         }
     }
 
-    fn norm(vector: ManifoldVector<Self>) -> f64 {
+    fn norm(&self, vector: ManifoldVector<Self>) -> f64 {
         (0..4)
-            .into_iter()
             .map(|i| vector.components.0[i].powi(2) * Self::METRIC[i])
             .sum::<f64>()
     }
 
-    fn inner(_root: Coord<Self>, a: FourVector, b: FourVector) -> f64 {
-        (0..4)
-            .into_iter()
-            .map(|i| a.0[i] * b.0[i] * Self::METRIC[i])
-            .sum()
+    fn inner(&self, _root: Coord<Self>, a: FourVector, b: FourVector) -> f64 {
+        (0..4).map(|i| a.0[i] * b.0[i] * Self::METRIC[i]).sum()
     }
 
-    fn from_local(
-        frame: ManifoldFrame<Self>,
-        vector: ManifoldVector<CarthesianMinkowski>,
-    ) -> ManifoldVector<Self> {
-        ManifoldVector {
-            root: Coord {
-                components: frame.root.components
-                    + (0..4)
-                        .into_iter()
-                        .map(|i| vector.root.components.0[i] * frame.axis[i])
-                        .sum(),
-                _metric: PhantomData,
-            },
-            components: (0..4)
-                .into_iter()
-                .map(|i| vector.components.0[i] * frame.axis[i])
-                .sum(),
+    fn christoffel(&self, _root: Coord<Self>) -> Christoffel {
+        Christoffel::default()
+    }
+}
+
+/// Checks shared by the tests of each metric
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::marker::PhantomData;
+
+    use super::{Christoffel, Metric, NumericMetric};
+    use crate::geometry::{Coord, FourVector, ManifoldFrame, ManifoldVector};
+
+    pub fn coord<T: Metric + ?Sized>(components: [f64; 4]) -> Coord<T> {
+        Coord {
+            components: FourVector(components),
+            _metric: PhantomData,
+        }
+    }
+
+    /// Christoffel symbols of the given metric tensor from finite differences
+    pub fn numeric_christoffel<T: Metric + ?Sized>(
+        metric_tensor: impl Fn(Coord<T>) -> [[f64; 4]; 4] + Sync,
+        root: Coord<T>,
+    ) -> Christoffel {
+        let numeric = NumericMetric::new(|x: FourVector| metric_tensor(coord(x.0)));
+        numeric.christoffel(coord(root.components.0))
+    }
+
+    /// Assert that analytic christoffel symbols match finite differences of
+    /// the metric tensor
+    pub fn assert_christoffel_matches_numeric<T: Metric + ?Sized>(
+        metric: &T,
+        metric_tensor: impl Fn(Coord<T>) -> [[f64; 4]; 4] + Sync,
+        points: &[[f64; 4]],
+    ) {
+        for &point in points {
+            let analytic = metric.christoffel(coord(point));
+            let numeric = numeric_christoffel(&metric_tensor, coord(point));
+            for mu in 0..4 {
+                for a in 0..4 {
+                    for b in 0..4 {
+                        let (x, y) = (analytic[mu][a][b], numeric[mu][a][b]);
+                        assert!(
+                            (x - y).abs() < 1e-6 * (1.0 + x.abs()),
+                            "gamma^{mu}_{a}{b} at {point:?}: analytic {x}, numeric {y}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Assert that a light ray sent from root along the given spatial
+    /// direction of a static observer stays null while it is integrated
+    pub fn assert_null_stays_null<T: Metric + ?Sized>(
+        metric: &T,
+        root: [f64; 4],
+        direction: [f64; 3],
+        step: f64,
+        steps: usize,
+    ) {
+        let frame = ManifoldFrame::from_four_velocity(
+            metric,
+            coord(root),
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+        );
+        let length = direction.iter().map(|c| c * c).sum::<f64>().sqrt();
+        let mut ray = ManifoldVector {
+            root: frame.root,
+            components: frame.axis[0]
+                + (0..3)
+                    .map(|i| direction[i] / length * frame.axis[i + 1])
+                    .sum(),
+        };
+        let scale = ray.components.0[0].powi(2);
+        assert!(metric.norm(ray).abs() < 1e-12 * scale);
+        for _ in 0..steps {
+            ray = metric.step_geodesic(ray, step);
+            assert!(!metric.captured(ray), "ray fell into the hole");
         }
+        let norm = metric.norm(ray) / scale;
+        assert!(norm.abs() < 1e-6, "norm {norm} after {steps} steps");
     }
 }
@@ -0,0 +1,365 @@
+use std::array;
+
+use crate::{
+    geometry::{Coord, FourVector, ManifoldVector, SpatialVec},
+    metric::{
+        Axisymmetric, Christoffel, Metric, MetricName, christoffel_from_derivatives, contract,
+    },
+};
+
+/// Outer horizon radius of a kerr black hole
+fn outer_horizon(mass: f64, spin: f64) -> f64 {
+    mass + (mass * mass - spin * spin).sqrt()
+}
+
+/// Kerr spacetime around a rotating mass, in boyer-lindquist coordinates
+/// `(t, r, theta, phi)`.
+///
+/// The spin is the angular momentum per unit mass `a = J/M`, with the black
+/// hole rotating in the direction of increasing phi. Note that bounding boxes
+/// are in terms of the boyer-lindquist coordinates, and that colliders which
+/// assume carthesian coordinates will not work in this chart. The coordinates
+/// are singular at the horizon and on the axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kerr {
+    pub mass: f64,
+    pub spin: f64,
+}
+
+impl Kerr {
+    pub fn new(mass: f64, spin: f64) -> Self {
+        Kerr { mass, spin }
+    }
+
+    /// Outer horizon radius `r_+ = M + sqrt(M^2 - a^2)`
+    pub fn horizon(&self) -> f64 {
+        outer_horizon(self.mass, self.spin)
+    }
+
+    /// Covariant metric tensor `g_{mu nu}` at root
+    pub fn metric_tensor(&self, root: Coord<Self>) -> [[f64; 4]; 4] {
+        let (m, a) = (self.mass, self.spin);
+        let [_, r, theta, _] = root.components.0;
+        let (s, c) = theta.sin_cos();
+        let sigma = r * r + a * a * c * c;
+        let delta = r * r - 2.0 * m * r + a * a;
+
+        let mut g = [[0.0; 4]; 4];
+        g[0][0] = -(1.0 - 2.0 * m * r / sigma);
+        g[0][3] = -2.0 * m * a * r * s * s / sigma;
+        g[3][0] = g[0][3];
+        g[1][1] = sigma / delta;
+        g[2][2] = sigma;
+        g[3][3] = (r * r + a * a + 2.0 * m * a * a * r * s * s / sigma) * s * s;
+        g
+    }
+
+    /// Contravariant metric tensor `g^{mu nu}` at root
+    pub fn inverse_metric_tensor(&self, root: Coord<Self>) -> [[f64; 4]; 4] {
+        let g = self.metric_tensor(root);
+        let det = g[0][0] * g[3][3] - g[0][3] * g[0][3];
+
+        let mut ginv = [[0.0; 4]; 4];
+        ginv[0][0] = g[3][3] / det;
+        ginv[0][3] = -g[0][3] / det;
+        ginv[3][0] = ginv[0][3];
+        ginv[3][3] = g[0][0] / det;
+        ginv[1][1] = 1.0 / g[1][1];
+        ginv[2][2] = 1.0 / g[2][2];
+        ginv
+    }
+}
+
+impl MetricName for Kerr {
+    const NAME: &'static str = "kerr";
+}
+
+impl Metric for Kerr {
+    fn norm(&self, vector: ManifoldVector<Self>) -> f64 {
+        self.inner(vector.root, vector.components, vector.components)
+    }
+
+    fn inner(&self, root: Coord<Self>, a: FourVector, b: FourVector) -> f64 {
+        contract(self.metric_tensor(root), a, b)
+    }
+
+    fn captured(&self, vector: ManifoldVector<Self>) -> bool {
+        // Coordinates degenerate at the horizon, so stop just outside it.
+        let r = vector.root.components.0[1];
+        r.is_nan() || r < self.horizon() * (1.0 + 1e-2)
+    }
+
+    fn escape_direction(&self, vector: ManifoldVector<Self>) -> SpatialVec {
+        // Far away boyer-lindquist coordinates are ordinary spherical ones
+        let [_, r, theta, phi] = vector.root.components.0;
+        let [_, dr, dtheta, dphi] = vector.components.0;
+        let (st, ct) = theta.sin_cos();
+        let (sp, cp) = phi.sin_cos();
+        let radial = [st * cp, st * sp, ct];
+        let polar = [ct * cp, ct * sp, -st];
+        let azimuthal = [-sp, cp, 0.0];
+        let d: [f64; 3] = array::from_fn(|i| {
+            dr * radial[i] + r * dtheta * polar[i] + r * st * dphi * azimuthal[i]
+        });
+        let length = d.iter().map(|c| c * c).sum::<f64>().sqrt();
+        SpatialVec(d.map(|c| c / length))
+    }
+
+    fn christoffel(&self, root: Coord<Self>) -> Christoffel {
+        let (m, a) = (self.mass, self.spin);
+        let [_, r, theta, _] = root.components.0;
+        let (s, c) = theta.sin_cos();
+        let sigma = r * r + a * a * c * c;
+        let delta = r * r - 2.0 * m * r + a * a;
+        let sigma_r = 2.0 * r;
+        let sigma_th = -2.0 * a * a * s * c;
+        let delta_r = 2.0 * r - 2.0 * m;
+        let sigma2 = sigma * sigma;
+
+        // dg[k][mu][nu] = d_k g_{mu nu}, only r and theta derivatives are nonzero
+        let mut dg = [[[0.0; 4]; 4]; 4];
+
+        dg[1][0][0] = 2.0 * m * (sigma - r * sigma_r) / sigma2;
+        dg[2][0][0] = -2.0 * m * r * sigma_th / sigma2;
+
+        dg[1][0][3] = -2.0 * m * a * s * s * (sigma - r * sigma_r) / sigma2;
+        dg[2][0][3] = -2.0 * m * a * r * (2.0 * s * c * sigma - s * s * sigma_th) / sigma2;
+        dg[1][3][0] = dg[1][0][3];
+        dg[2][3][0] = dg[2][0][3];
+
+        dg[1][1][1] = (sigma_r * delta - sigma * delta_r) / (delta * delta);
+        dg[2][1][1] = sigma_th / delta;
+
+        dg[1][2][2] = sigma_r;
+        dg[2][2][2] = sigma_th;
+
+        dg[1][3][3] =
+            2.0 * r * s * s + 2.0 * m * a * a * s.powi(4) * (sigma - r * sigma_r) / sigma2;
+        dg[2][3][3] = 2.0 * (r * r + a * a) * s * c
+            + 2.0 * m * a * a * r * (4.0 * s.powi(3) * c * sigma - s.powi(4) * sigma_th) / sigma2;
+
+        christoffel_from_derivatives(self.inverse_metric_tensor(root), dg)
+    }
+}
+
+impl Axisymmetric for Kerr {
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    fn spin(&self) -> f64 {
+        self.spin
+    }
+
+    fn equatorial(&self, root: Coord<Self>) -> [f64; 3] {
+        let [_, r, theta, phi] = root.components.0;
+        [r, phi, r * theta.cos()]
+    }
+
+    fn rotation(&self, _root: Coord<Self>) -> FourVector {
+        FourVector([0.0, 0.0, 0.0, 1.0])
+    }
+}
+
+/// Kerr spacetime around a rotating mass, in carthesian kerr-schild coordinates
+/// `(t, x, y, z)`, with the spin axis along z.
+///
+/// The metric takes the form `g = eta + f l l` with `l` a null vector. This
+/// uses the outgoing form of the coordinates, which is regular across the past
+/// horizon. That is the horizon that rays traced backwards from a camera run
+/// into, so they can cross it cleanly. Far from the hole the coordinates are
+/// approximately carthesian, so bounding boxes and colliders behave as in flat
+/// space. The spin is the angular momentum per unit mass `a = J/M`, with the
+/// black hole rotating counterclockwise around the positive z axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KerrSchild {
+    pub mass: f64,
+    pub spin: f64,
+}
+
+impl KerrSchild {
+    const ETA: [f64; 4] = [-1.0, 1.0, 1.0, 1.0];
+
+    pub fn new(mass: f64, spin: f64) -> Self {
+        KerrSchild { mass, spin }
+    }
+
+    /// Outer horizon, in terms of the spheroidal radius `r`
+    pub fn horizon(&self) -> f64 {
+        outer_horizon(self.mass, self.spin)
+    }
+
+    /// Spheroidal radius `r`, the boyer-lindquist radius of the point
+    pub fn radius(&self, root: Coord<Self>) -> f64 {
+        let [_, x, y, z] = root.components.0;
+        let a2 = self.spin * self.spin;
+        let b = x * x + y * y + z * z - a2;
+        (0.5 * b + (0.25 * b * b + a2 * z * z).sqrt()).sqrt()
+    }
+
+    /// Scalar function f and covector l at root
+    fn potential(&self, root: Coord<Self>) -> (f64, [f64; 4]) {
+        let [_, x, y, z] = root.components.0;
+        let (m, a) = (self.mass, self.spin);
+        let r = self.radius(root);
+        let q = r * r + a * a;
+        let f = 2.0 * m * r.powi(3) / (r.powi(4) + a * a * z * z);
+        (f, [1.0, -(r * x - a * y) / q, -(r * y + a * x) / q, -z / r])
+    }
+
+    /// Covariant metric tensor `g_{mu nu}` at root
+    pub fn metric_tensor(&self, root: Coord<Self>) -> [[f64; 4]; 4] {
+        let (f, l) = self.potential(root);
+        array::from_fn(|mu| {
+            array::from_fn(|nu| (if mu == nu { Self::ETA[mu] } else { 0.0 }) + f * l[mu] * l[nu])
+        })
+    }
+
+    /// Contravariant metric tensor `g^{mu nu}` at root
+    pub fn inverse_metric_tensor(&self, root: Coord<Self>) -> [[f64; 4]; 4] {
+        let (f, l) = self.potential(root);
+        let lup: [f64; 4] = array::from_fn(|mu| Self::ETA[mu] * l[mu]);
+        array::from_fn(|mu| {
+            array::from_fn(|nu| {
+                (if mu == nu { Self::ETA[mu] } else { 0.0 }) - f * lup[mu] * lup[nu]
+            })
+        })
+    }
+}
+
+impl MetricName for KerrSchild {
+    const NAME: &'static str = "kerr_schild";
+}
+
+impl Metric for KerrSchild {
+    fn norm(&self, vector: ManifoldVector<Self>) -> f64 {
+        self.inner(vector.root, vector.components, vector.components)
+    }
+
+    fn inner(&self, root: Coord<Self>, a: FourVector, b: FourVector) -> f64 {
+        contract(self.metric_tensor(root), a, b)
+    }
+
+    fn captured(&self, vector: ManifoldVector<Self>) -> bool {
+        let r = self.radius(vector.root);
+        r.is_nan() || r < self.horizon()
+    }
+
+    fn christoffel(&self, root: Coord<Self>) -> Christoffel {
+        let [_, x, y, z] = root.components.0;
+        let (m, a) = (self.mass, self.spin);
+        let p = [x, y, z];
+        let rho2 = x * x + y * y + z * z;
+        let r = self.radius(root);
+        let (f, l) = self.potential(root);
+        let delta = |i: usize, j: usize| if i == j { 1.0 } else { 0.0 };
+
+        // Implicit differentiation of r^4 - (rho^2 - a^2) r^2 - a^2 z^2 = 0
+        let dr: [f64; 3] = array::from_fn(|k| {
+            (p[k] * r * r + a * a * z * delta(k, 2)) / (r * (2.0 * r * r - rho2 + a * a))
+        });
+
+        let q = r * r + a * a;
+        let den = r.powi(4) + a * a * z * z;
+        let mut df = [0.0; 4];
+        let mut dl = [[0.0; 4]; 4];
+        for k in 0..3 {
+            df[k + 1] = 2.0
+                * m
+                * (3.0 * r * r * dr[k] * den
+                    - r.powi(3) * (4.0 * r.powi(3) * dr[k] + 2.0 * a * a * z * delta(k, 2)))
+                / (den * den);
+
+            let dq = 2.0 * r * dr[k];
+            let dpx = dr[k] * x + r * delta(k, 0) - a * delta(k, 1);
+            let dpy = dr[k] * y + r * delta(k, 1) + a * delta(k, 0);
+            dl[k + 1][1] = -(dpx * q - (r * x - a * y) * dq) / (q * q);
+            dl[k + 1][2] = -(dpy * q - (r * y + a * x) * dq) / (q * q);
+            dl[k + 1][3] = -(delta(k, 2) * r - z * dr[k]) / (r * r);
+        }
+
+        let dg: [[[f64; 4]; 4]; 4] = array::from_fn(|k| {
+            array::from_fn(|mu| {
+                array::from_fn(|nu| {
+                    df[k] * l[mu] * l[nu] + f * (dl[k][mu] * l[nu] + l[mu] * dl[k][nu])
+                })
+            })
+        });
+
+        christoffel_from_derivatives(self.inverse_metric_tensor(root), dg)
+    }
+}
+
+/// The equatorial plane is `z = 0`. Time and azimuth differ from their
+/// boyer-lindquist counterparts by functions of `r` only, so both charts share
+/// the killing vectors and angular velocities of circular orbits.
+impl Axisymmetric for KerrSchild {
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    fn spin(&self) -> f64 {
+        self.spin
+    }
+
+    fn equatorial(&self, root: Coord<Self>) -> [f64; 3] {
+        let [_, x, y, z] = root.components.0;
+        [self.radius(root), y.atan2(x), z]
+    }
+
+    fn rotation(&self, root: Coord<Self>) -> FourVector {
+        let [_, x, y, _] = root.components.0;
+        FourVector([0.0, -y, x, 0.0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::testing::{assert_christoffel_matches_numeric, assert_null_stays_null};
+
+    #[test]
+    fn boyer_lindquist_christoffel_matches_numeric() {
+        let metric = Kerr::new(1.0, 0.9);
+        assert_christoffel_matches_numeric(
+            &metric,
+            |root| metric.metric_tensor(root),
+            &[
+                [0.0, 10.0, 1.2, 0.3],
+                [2.0, 4.0, 0.4, -2.0],
+                [0.0, 3.0, std::f64::consts::FRAC_PI_2, 1.0],
+                [-1.0, 25.0, 2.8, 5.0],
+            ],
+        );
+    }
+
+    #[test]
+    fn kerr_schild_christoffel_matches_numeric() {
+        let metric = KerrSchild::new(1.0, 0.9);
+        assert_christoffel_matches_numeric(
+            &metric,
+            |root| metric.metric_tensor(root),
+            &[
+                [0.0, 10.0, 0.0, 0.0],
+                [3.0, 2.0, -3.0, 4.0],
+                [0.0, -1.5, 2.5, -0.5],
+                [0.0, 0.0, 0.0, 6.0],
+                [-5.0, 20.0, 15.0, 30.0],
+            ],
+        );
+    }
+
+    #[test]
+    fn boyer_lindquist_null_ray_stays_null() {
+        let metric = Kerr::new(1.0, 0.9);
+        assert_null_stays_null(&metric, [0.0, 10.0, 1.2, 0.3], [0.2, 0.5, 1.0], 0.05, 400);
+        assert_null_stays_null(&metric, [0.0, 8.0, 1.5, 0.0], [-0.3, -0.2, -1.0], 0.05, 400);
+    }
+
+    #[test]
+    fn kerr_schild_null_ray_stays_null() {
+        let metric = KerrSchild::new(1.0, 0.9);
+        assert_null_stays_null(&metric, [0.0, 0.0, 0.0, -10.0], [1.0, 0.0, 0.5], 0.05, 400);
+        assert_null_stays_null(&metric, [0.0, 6.0, 2.0, 1.0], [-0.3, 1.0, 0.2], 0.05, 400);
+    }
+}
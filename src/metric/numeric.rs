@@ -0,0 +1,131 @@
+use std::array;
+
+use crate::{
+    geometry::{Coord, FourVector, ManifoldVector},
+    metric::{Christoffel, Metric, MetricName, christoffel_from_derivatives, contract},
+    util::invert4,
+};
+
+/// Metric given by a closure computing the covariant metric tensor
+/// `g_{mu nu}` from the coordinate components of a point.
+///
+/// Christoffel symbols are derived from central finite differences of the
+/// metric, with coordinate step `epsilon`. This makes it easy to try out a
+/// spacetime, at the cost of some accuracy and speed compared to a metric with
+/// analytic christoffel symbols.
+#[derive(Clone, Copy)]
+pub struct NumericMetric<F: Fn(FourVector) -> [[f64; 4]; 4]> {
+    pub metric: F,
+    pub epsilon: f64,
+}
+
+impl<F: Fn(FourVector) -> [[f64; 4]; 4] + Sync> NumericMetric<F> {
+    pub fn new(metric: F) -> Self {
+        NumericMetric {
+            metric,
+            epsilon: 1e-5,
+        }
+    }
+
+    /// Covariant metric tensor `g_{mu nu}` at root
+    pub fn metric_tensor(&self, root: Coord<Self>) -> [[f64; 4]; 4] {
+        (self.metric)(root.components)
+    }
+
+    /// Contravariant metric tensor `g^{mu nu}` at root
+    pub fn inverse_metric_tensor(&self, root: Coord<Self>) -> [[f64; 4]; 4] {
+        invert4(self.metric_tensor(root))
+    }
+}
+
+impl<F: Fn(FourVector) -> [[f64; 4]; 4]> std::fmt::Debug for NumericMetric<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NumericMetric")
+            .field("epsilon", &self.epsilon)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: Fn(FourVector) -> [[f64; 4]; 4]> MetricName for NumericMetric<F> {
+    const NAME: &'static str = "numeric";
+}
+
+impl<F: Fn(FourVector) -> [[f64; 4]; 4] + Sync> Metric for NumericMetric<F> {
+    fn norm(&self, vector: ManifoldVector<Self>) -> f64 {
+        self.inner(vector.root, vector.components, vector.components)
+    }
+
+    fn inner(&self, root: Coord<Self>, a: FourVector, b: FourVector) -> f64 {
+        contract(self.metric_tensor(root), a, b)
+    }
+
+    fn christoffel(&self, root: Coord<Self>) -> Christoffel {
+        let x = root.components;
+        let dg: [[[f64; 4]; 4]; 4] = array::from_fn(|k| {
+            let mut offset = FourVector::default();
+            offset.0[k] = self.epsilon;
+            let forward = (self.metric)(x + offset);
+            let backward = (self.metric)(x - offset);
+            array::from_fn(|mu| {
+                array::from_fn(|nu| (forward[mu][nu] - backward[mu][nu]) / (2.0 * self.epsilon))
+            })
+        });
+
+        christoffel_from_derivatives(self.inverse_metric_tensor(root), dg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::{
+        Schwarzschild,
+        testing::{assert_null_stays_null, coord},
+    };
+
+    /// Flat spacetime in spherical coordinates `(t, r, theta, phi)`
+    fn spherical(x: FourVector) -> [[f64; 4]; 4] {
+        let [_, r, theta, _] = x.0;
+        let mut g = [[0.0; 4]; 4];
+        g[0][0] = -1.0;
+        g[1][1] = 1.0;
+        g[2][2] = r * r;
+        g[3][3] = (r * theta.sin()).powi(2);
+        g
+    }
+
+    #[test]
+    fn christoffel_matches_spherical_coordinates() {
+        let metric = NumericMetric::new(spherical);
+        for [r, theta] in [[1.0_f64, 0.5], [5.0, 1.3], [20.0, 2.9]] {
+            let (s, c) = theta.sin_cos();
+            let mut exact = Christoffel::default();
+            exact[1][2][2] = -r;
+            exact[1][3][3] = -r * s * s;
+            exact[2][1][2] = 1.0 / r;
+            exact[2][2][1] = 1.0 / r;
+            exact[2][3][3] = -s * c;
+            exact[3][1][3] = 1.0 / r;
+            exact[3][3][1] = 1.0 / r;
+            exact[3][2][3] = c / s;
+            exact[3][3][2] = c / s;
+
+            let numeric = metric.christoffel(coord([0.0, r, theta, 0.7]));
+            for mu in 0..4 {
+                for a in 0..4 {
+                    for b in 0..4 {
+                        let (x, y) = (exact[mu][a][b], numeric[mu][a][b]);
+                        assert!((x - y).abs() < 1e-6 * (1.0 + x.abs()), "{x} != {y}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn null_ray_stays_null() {
+        let schwarzschild = Schwarzschild::new(1.0);
+        let metric = NumericMetric::new(|x: FourVector| schwarzschild.metric_tensor(coord(x.0)));
+        assert_null_stays_null(&metric, [0.0, 0.0, 0.0, -10.0], [1.0, 0.0, 0.5], 0.05, 400);
+    }
+}
@@ -0,0 +1,155 @@
+use std::array;
+
+use crate::{
+    geometry::{Coord, FourVector, ManifoldVector},
+    metric::{
+        Axisymmetric, Christoffel, Metric, MetricName, christoffel_from_derivatives, contract,
+    },
+};
+
+/// Schwarzschild spacetime around a non-rotating, uncharged mass.
+///
+/// Uses schwarzschild coordinates with the angular part written in carthesian
+/// form, i.e. coordinates are `(t, x, y, z)` with `r = sqrt(x^2 + y^2 + z^2)`
+/// and line element
+/// `ds^2 = -(1 - 2M/r) dt^2 + (1 - 2M/r)^-1 dr^2 + r^2 dOmega^2`.
+/// This keeps bounding boxes and colliders working as they do in flat space.
+/// The coordinates are singular at the horizon `r = 2M`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schwarzschild {
+    pub mass: f64,
+}
+
+impl Schwarzschild {
+    pub fn new(mass: f64) -> Self {
+        Schwarzschild { mass }
+    }
+
+    /// Schwarzschild radius `2M`
+    pub fn horizon(&self) -> f64 {
+        2.0 * self.mass
+    }
+
+    /// Radius and unit radial direction of a coordinate
+    fn radial(root: Coord<Self>) -> (f64, [f64; 3]) {
+        let p = root.components.0;
+        let r = (p[1] * p[1] + p[2] * p[2] + p[3] * p[3]).sqrt();
+        (r, [p[1] / r, p[2] / r, p[3] / r])
+    }
+
+    /// Covariant metric tensor `g_{mu nu}` at root
+    pub fn metric_tensor(&self, root: Coord<Self>) -> [[f64; 4]; 4] {
+        let (r, n) = Self::radial(root);
+        let alpha = 1.0 - 2.0 * self.mass / r;
+        let h = 1.0 / alpha - 1.0;
+        array::from_fn(|mu| {
+            array::from_fn(|nu| match (mu, nu) {
+                (0, 0) => -alpha,
+                (0, _) | (_, 0) => 0.0,
+                (i, j) => (if i == j { 1.0 } else { 0.0 }) + h * n[i - 1] * n[j - 1],
+            })
+        })
+    }
+
+    /// Contravariant metric tensor `g^{mu nu}` at root
+    pub fn inverse_metric_tensor(&self, root: Coord<Self>) -> [[f64; 4]; 4] {
+        let (r, n) = Self::radial(root);
+        let alpha = 1.0 - 2.0 * self.mass / r;
+        array::from_fn(|mu| {
+            array::from_fn(|nu| match (mu, nu) {
+                (0, 0) => -1.0 / alpha,
+                (0, _) | (_, 0) => 0.0,
+                (i, j) => (if i == j { 1.0 } else { 0.0 }) - (1.0 - alpha) * n[i - 1] * n[j - 1],
+            })
+        })
+    }
+}
+
+impl MetricName for Schwarzschild {
+    const NAME: &'static str = "schwarzschild";
+}
+
+impl Metric for Schwarzschild {
+    fn norm(&self, vector: ManifoldVector<Self>) -> f64 {
+        self.inner(vector.root, vector.components, vector.components)
+    }
+
+    fn inner(&self, root: Coord<Self>, a: FourVector, b: FourVector) -> f64 {
+        contract(self.metric_tensor(root), a, b)
+    }
+
+    fn captured(&self, vector: ManifoldVector<Self>) -> bool {
+        // Coordinates degenerate at the horizon, so stop just outside it.
+        let (r, _) = Self::radial(vector.root);
+        r.is_nan() || r < self.horizon() * (1.0 + 1e-2)
+    }
+
+    fn christoffel(&self, root: Coord<Self>) -> Christoffel {
+        let m = self.mass;
+        let (r, n) = Self::radial(root);
+        let h = 2.0 * m / (r - 2.0 * m);
+        let dh = -2.0 * m / (r - 2.0 * m).powi(2);
+        let delta = |i: usize, j: usize| if i == j { 1.0 } else { 0.0 };
+
+        // dg[k][mu][nu] = d_k g_{mu nu}, only spatial derivatives are nonzero
+        let mut dg = [[[0.0; 4]; 4]; 4];
+        for k in 0..3 {
+            dg[k + 1][0][0] = -2.0 * m / (r * r) * n[k];
+            for i in 0..3 {
+                for j in 0..3 {
+                    dg[k + 1][i + 1][j + 1] = dh * n[k] * n[i] * n[j]
+                        + h * (delta(i, k) * n[j] + delta(j, k) * n[i] - 2.0 * n[i] * n[j] * n[k])
+                            / r;
+                }
+            }
+        }
+
+        christoffel_from_derivatives(self.inverse_metric_tensor(root), dg)
+    }
+}
+
+/// The equatorial plane is `z = 0`
+impl Axisymmetric for Schwarzschild {
+    fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    fn equatorial(&self, root: Coord<Self>) -> [f64; 3] {
+        let (r, _) = Self::radial(root);
+        let [_, x, y, z] = root.components.0;
+        [r, y.atan2(x), z]
+    }
+
+    fn rotation(&self, root: Coord<Self>) -> FourVector {
+        let [_, x, y, _] = root.components.0;
+        FourVector([0.0, -y, x, 0.0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::testing::{assert_christoffel_matches_numeric, assert_null_stays_null};
+
+    #[test]
+    fn christoffel_matches_numeric() {
+        let metric = Schwarzschild::new(1.0);
+        assert_christoffel_matches_numeric(
+            &metric,
+            |root| metric.metric_tensor(root),
+            &[
+                [0.0, 10.0, 0.0, 0.0],
+                [3.0, 2.0, -3.0, 4.0],
+                [0.0, -1.5, 2.5, -6.0],
+                [-5.0, 20.0, 15.0, 30.0],
+            ],
+        );
+    }
+
+    #[test]
+    fn null_ray_stays_null() {
+        let metric = Schwarzschild::new(1.0);
+        assert_null_stays_null(&metric, [0.0, 0.0, 0.0, -10.0], [1.0, 0.0, 0.5], 0.05, 400);
+        assert_null_stays_null(&metric, [0.0, 6.0, 2.0, 1.0], [-0.3, 1.0, 0.2], 0.05, 400);
+    }
+}
@@ -1,17 +1,168 @@
 use std::marker::PhantomData;
 
 use crate::{
-    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame, ManifoldVector},
-    metric::Metric,
-    util::sqr,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame, ManifoldVector, SpatialVec},
+    metric::{CarthesianMinkowski, Metric},
+    spectrum::Emission,
+    util::{dot3, sqr},
 };
 
-pub trait RayIntersector<T: Metric + ?Sized> {
-    fn intersects(&self, ray: ManifoldVector<T>, stepsize: f64) -> bool;
+mod csg;
+mod disk;
+mod mesh;
+mod primitives;
 
-    fn in_bounding_box(&self, bbox: BoundingBox<T>) -> bool;
+pub use csg::{Csg, Operation};
+pub use disk::{AccretionDisk, Orbits};
+pub use mesh::{Mesh, read_obj, read_ply};
+pub use primitives::{BoxCollider, CylinderCollider, DiscCollider, PlaneCollider, TorusCollider};
+
+/// Details of where and how a ray hit an object
+#[derive(Debug)]
+pub struct Hit<T: Metric + ?Sized> {
+    /// Affine parameter along the tested ray segment at which the hit occurs
+    pub affine: f64,
+    /// Event of the hit, in manifold coordinates
+    pub event: Coord<T>,
+    /// Hit point in the local rest frame of the object
+    pub position: SpatialVec,
+    /// Outward surface normal in the local rest frame of the object
+    pub normal: SpatialVec,
+    /// Direction of the ray in the local rest frame of the object
+    pub direction: FourVector,
+    /// Proper time of the object at the hit
+    pub proper_time: f64,
+}
+
+impl<T: Metric + ?Sized> Clone for Hit<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Metric + ?Sized> Copy for Hit<T> {}
+
+/// Part of a ray segment that lies inside an object
+#[derive(Debug)]
+pub struct Interval<T: Metric + ?Sized> {
+    /// Affine parameter at which the ray enters the object
+    pub start: f64,
+    /// Affine parameter at which the ray leaves the object
+    pub end: f64,
+    /// Hit where the ray enters, none if the interval is cut short by the
+    /// segment or the lifetime of the object
+    pub entry: Option<Hit<T>>,
+    /// Hit where the ray leaves, none if the interval is cut short by the
+    /// segment or the lifetime of the object
+    pub exit: Option<Hit<T>>,
+}
+
+impl<T: Metric + ?Sized> Clone for Interval<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
 }
 
+impl<T: Metric + ?Sized> Copy for Interval<T> {}
+
+/// Object that rays can hit.
+pub trait RayIntersector<T: Metric + ?Sized>: Sync {
+    /// First hit of the ray with the object within stepsize of the ray root,
+    /// if any.
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>>;
+
+    /// Parts of the ray within stepsize of its root that lie inside the
+    /// object, sorted and disjoint, as used by constructive solid geometry.
+    /// Objects without an inside report an empty interval at their hit.
+    fn intervals(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Vec<Interval<T>> {
+        self.intersects(metric, ray, stepsize)
+            .map(|hit| Interval {
+                start: hit.affine,
+                end: hit.affine,
+                entry: Some(hit),
+                exit: Some(hit),
+            })
+            .into_iter()
+            .collect()
+    }
+
+    /// Emission at a hit, for objects whose emission varies over their
+    /// surface. Overrides the emission the object was added to the scene with.
+    fn emission(&self, _metric: &T, _hit: &Hit<T>) -> Option<Emission> {
+        None
+    }
+
+    /// Whether the object may be hit anywhere within the coordinate box. May
+    /// give false positives, but never false negatives.
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool;
+}
+
+/// Affine parameter range within `[0, stepsize]` of a ray, given in the local
+/// rest frame of an object, during which the object exists. Objects live for
+/// local times between `-time_thickness` and `time_thickness`. The range is
+/// empty if lower exceeds upper.
+pub(crate) fn lifetime(
+    local_ray: ManifoldVector<CarthesianMinkowski>,
+    stepsize: f64,
+    time_thickness: f64,
+) -> (f64, f64) {
+    let t1 = (-time_thickness - local_ray.root.components.0[0]) / local_ray.components.0[0];
+    let t2 = (time_thickness - local_ray.root.components.0[0]) / local_ray.components.0[0];
+    (t1.min(t2).max(0.0), t1.max(t2).min(stepsize))
+}
+
+/// Bounds of a coordinate box in the local coordinates of a frame, as lower
+/// and upper corner of the box around its mapped corners
+pub(crate) fn local_box<T: Metric + ?Sized>(
+    metric: &T,
+    frame: ManifoldFrame<T>,
+    bbox: BoundingBox<T>,
+) -> ([f64; 4], [f64; 4]) {
+    let mut upper = [f64::MIN; 4];
+    let mut lower = [f64::MAX; 4];
+    for corner in 0..16 {
+        let local = metric.into_local(
+            frame,
+            ManifoldVector {
+                root: Coord {
+                    components: FourVector(std::array::from_fn(|i| {
+                        bbox.bbox[i][(corner >> i) & 1]
+                    })),
+                    _metric: PhantomData,
+                },
+                components: FourVector::default(),
+            },
+        );
+        for i in 0..4 {
+            upper[i] = upper[i].max(local.root.components.0[i]);
+            lower[i] = lower[i].min(local.root.components.0[i]);
+        }
+    }
+    (lower, upper)
+}
+
+/// Whether an object reaching at most `extent` from the origin of its frame
+/// along each local axis may be hit within the coordinate box
+pub(crate) fn in_extent<T: Metric + ?Sized>(
+    metric: &T,
+    frame: ManifoldFrame<T>,
+    bbox: BoundingBox<T>,
+    extent: [f64; 3],
+    time_thickness: f64,
+) -> bool {
+    let (lower, upper) = local_box(metric, frame, bbox);
+    // Generous margins, as into_local is only a first order approximation
+    let margin = extent.iter().copied().fold(0.0, f64::max);
+    lower[0] < 2.0 * time_thickness
+        && upper[0] > -2.0 * time_thickness
+        && (0..3).all(|i| lower[i + 1] < extent[i] + margin && upper[i + 1] > -extent[i] - margin)
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "T: crate::metric::MetricName")
+)]
 pub struct SphereCollider<T: Metric + ?Sized> {
     pub center: ManifoldFrame<T>,
     pub radius: f64,
@@ -19,11 +170,9 @@ pub struct SphereCollider<T: Metric + ?Sized> {
 }
 
 impl<T: Metric + ?Sized> RayIntersector<T> for SphereCollider<T> {
-    fn intersects(&self, ray: ManifoldVector<T>, stepsize: f64) -> bool {
-        let mut lower: f64 = 0.0;
-        let mut upper: f64 = stepsize;
-
-        let local_ray = T::into_local(self.center, ray);
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        let local_ray = metric.into_local(self.center, ray);
+        let (mut lower, mut upper) = lifetime(local_ray, stepsize, self.time_thickness);
 
         let ab = (0..3)
             .map(|i| local_ray.root.components.0[i + 1] * local_ray.components.0[i + 1])
@@ -39,61 +188,138 @@ impl<T: Metric + ?Sized> RayIntersector<T> for SphereCollider<T> {
         let d = sqr(ab) - na * nb + nb * r2;
 
         if d < 0.0 {
-            return false;
+            return None;
         }
 
         let rootd = d.sqrt();
 
-        lower = lower.max((-ab - rootd) / sqr(nb));
-        upper = upper.min((-ab + rootd) / sqr(nb));
+        lower = lower.max((-ab - rootd) / nb);
+        upper = upper.min((-ab + rootd) / nb);
+
+        if lower > upper {
+            return None;
+        }
+
+        let local = local_ray.root.components + lower * local_ray.components;
+        let position = SpatialVec([local.0[1], local.0[2], local.0[3]]);
+        let length = position.0.iter().map(|v| sqr(*v)).sum::<f64>().sqrt();
+
+        Some(Hit {
+            affine: lower,
+            event: Coord {
+                components: ray.root.components + lower * ray.components,
+                _metric: PhantomData,
+            },
+            position,
+            normal: SpatialVec(position.0.map(|v| v / length)),
+            direction: local_ray.components,
+            proper_time: local.0[0],
+        })
+    }
+
+    fn intervals(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Vec<Interval<T>> {
+        let local_ray = metric.into_local(self.center, ray);
+        let (origin, direction) = primitives::spatial(local_ray);
+        let window = lifetime(local_ray, stepsize, self.time_thickness);
+
+        let (ab, nb) = (dot3(origin, direction), dot3(direction, direction));
+        let d = sqr(ab) - nb * (dot3(origin, origin) - sqr(self.radius));
+        if d < 0.0 {
+            return vec![];
+        }
+        let crossings = [-1.0, 1.0]
+            .map(|sign| {
+                let s = (-ab + sign * d.sqrt()) / nb;
+                let normal = std::array::from_fn(|i| (origin[i] + s * direction[i]) / self.radius);
+                (s, normal)
+            })
+            .to_vec();
+        primitives::convex_intervals(ray, local_ray, window, crossings)
+    }
 
-        let t1 = (local_ray.root.components.0[0] - self.time_thickness) / local_ray.components.0[0];
-        let t2 = (local_ray.root.components.0[0] + self.time_thickness) / local_ray.components.0[0];
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool {
+        in_extent(
+            metric,
+            self.center,
+            bbox,
+            [self.radius; 3],
+            self.time_thickness,
+        )
+    }
+}
 
-        lower = lower.max(t1.min(t2));
-        upper = upper.min(t1.max(t2));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::{CarthesianMinkowski, testing::coord};
 
-        lower <= upper
+    fn sphere(position: [f64; 4], radius: f64) -> SphereCollider<CarthesianMinkowski> {
+        SphereCollider {
+            center: ManifoldFrame::from_four_velocity(
+                &CarthesianMinkowski,
+                coord(position),
+                FourVector([1.0, 0.0, 0.0, 0.0]),
+            ),
+            radius,
+            time_thickness: 10.0,
+        }
     }
 
-    fn in_bounding_box(&self, bbox: BoundingBox<T>) -> bool {
-        let mut upper = [f64::MIN; 4];
-        let mut lower = [f64::MAX; 4];
-        for i0 in 0..1 {
-            for i1 in 0..1 {
-                for i2 in 0..1 {
-                    for i3 in 0..1 {
-                        let local = T::into_local(
-                            self.center,
-                            ManifoldVector {
-                                root: Coord {
-                                    components: FourVector([
-                                        bbox.bbox[0][i0],
-                                        bbox.bbox[1][i1],
-                                        bbox.bbox[2][i2],
-                                        bbox.bbox[3][i3],
-                                    ]),
-                                    _metric: PhantomData,
-                                },
-                                components: FourVector::default(),
-                            },
-                        );
-                        for i in 0..4 {
-                            upper[i] = upper[i].max(local.root.components.0[i]);
-                            lower[i] = lower[i].min(local.root.components.0[i]);
-                        }
-                    }
-                }
-            }
+    fn bbox(bbox: [[f64; 2]; 4]) -> BoundingBox<CarthesianMinkowski> {
+        BoundingBox {
+            bbox,
+            _metric: PhantomData,
         }
+    }
+
+    #[test]
+    fn sphere_in_bounding_box() {
+        let sphere = sphere([0.0, 10.0, -5.0, 3.0], 1.0);
+        let metric = CarthesianMinkowski;
+        let inside = |b| sphere.in_bounding_box(&metric, bbox(b));
+
+        // Around the sphere, and overlapping it from one side
+        assert!(inside([[-1.0, 1.0], [9.0, 11.0], [-6.0, -4.0], [2.0, 4.0]]));
+        assert!(inside([
+            [-1.0, 1.0],
+            [10.5, 20.0],
+            [-6.0, -4.0],
+            [2.0, 4.0]
+        ]));
+        assert!(inside([[-1.0, 1.0], [0.0, 9.5], [-20.0, 0.0], [0.0, 3.0]]));
+        // Far away along a single axis, every axis must be checked on its own
+        for axis in 1..4 {
+            let mut far = [[-1.0, 1.0], [9.0, 11.0], [-6.0, -4.0], [2.0, 4.0]];
+            far[axis] = [50.0, 60.0];
+            assert!(!inside(far), "far along axis {axis}");
+            far[axis] = [-60.0, -50.0];
+            assert!(!inside(far), "far along axis {axis}");
+        }
+        // Outside the lifetime of the sphere
+        assert!(!inside([
+            [30.0, 40.0],
+            [9.0, 11.0],
+            [-6.0, -4.0],
+            [2.0, 4.0]
+        ]));
+        assert!(!inside([
+            [-40.0, -30.0],
+            [9.0, 11.0],
+            [-6.0, -4.0],
+            [2.0, 4.0]
+        ]));
+    }
 
-        lower[0] < 2.0 * self.time_thickness
-            && upper[0] > -2.0 * self.time_thickness
-            && (lower[1].abs().min(upper[1].abs()) < 2.0 * self.radius
-                || lower[1].signum() != upper[1].signum())
-            && (lower[1].abs().min(upper[2].abs()) < 2.0 * self.radius
-                || lower[2].signum() != upper[2].signum())
-            && (lower[1].abs().min(upper[3].abs()) < 2.0 * self.radius
-                || lower[3].signum() != upper[3].signum())
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sphere_toml_round_trip() {
+        let sphere = sphere([1.0, 2.0, 3.0, 4.0], 1.5);
+        let text = toml::to_string(&sphere).unwrap();
+        let read: SphereCollider<CarthesianMinkowski> = toml::from_str(&text).unwrap();
+        assert_eq!(read.center.root.components, sphere.center.root.components);
+        assert_eq!(read.center.axis, sphere.center.axis);
+        assert_eq!(read.radius, sphere.radius);
+        assert_eq!(read.time_thickness, sphere.time_thickness);
+        assert!(toml::from_str::<SphereCollider<crate::metric::Schwarzschild>>(&text).is_err());
     }
 }
@@ -0,0 +1,244 @@
+use crate::{
+    geometry::{BoundingBox, ManifoldVector, SpatialVec},
+    metric::Metric,
+    objects::{Hit, Interval, RayIntersector},
+};
+
+/// Boolean operation combining two objects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Inside either object
+    Union,
+    /// Inside both objects
+    Intersection,
+    /// Inside the first object but not the second
+    Difference,
+}
+
+impl Operation {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Operation::Union => a || b,
+            Operation::Intersection => a && b,
+            Operation::Difference => a && !b,
+        }
+    }
+}
+
+/// Constructive solid geometry: two objects combined by a boolean operation
+/// on the intervals each ray segment spends inside them. Either may be a
+/// `Csg` itself.
+///
+/// Objects without an inside only contribute their surface. The combination
+/// is colored as a whole by the emission it was added to the scene with.
+pub struct Csg<T: Metric + ?Sized> {
+    pub operation: Operation,
+    pub a: Box<dyn RayIntersector<T>>,
+    pub b: Box<dyn RayIntersector<T>>,
+}
+
+impl<T: Metric + ?Sized> Csg<T> {
+    pub fn new(
+        operation: Operation,
+        a: impl RayIntersector<T> + 'static,
+        b: impl RayIntersector<T> + 'static,
+    ) -> Self {
+        Csg {
+            operation,
+            a: Box::new(a),
+            b: Box::new(b),
+        }
+    }
+
+    pub fn union(a: impl RayIntersector<T> + 'static, b: impl RayIntersector<T> + 'static) -> Self {
+        Self::new(Operation::Union, a, b)
+    }
+
+    pub fn intersection(
+        a: impl RayIntersector<T> + 'static,
+        b: impl RayIntersector<T> + 'static,
+    ) -> Self {
+        Self::new(Operation::Intersection, a, b)
+    }
+
+    pub fn difference(
+        a: impl RayIntersector<T> + 'static,
+        b: impl RayIntersector<T> + 'static,
+    ) -> Self {
+        Self::new(Operation::Difference, a, b)
+    }
+}
+
+impl<T: Metric + ?Sized> RayIntersector<T> for Csg<T> {
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        self.intervals(metric, ray, stepsize)
+            .into_iter()
+            .flat_map(|interval| [interval.entry, interval.exit])
+            .flatten()
+            .next()
+    }
+
+    fn intervals(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Vec<Interval<T>> {
+        // Boundaries of both objects in order, entries before exits at the
+        // same parameter so that touching intervals merge
+        let mut events = vec![];
+        for (object, intervals) in [
+            (0, self.a.intervals(metric, ray, stepsize)),
+            (1, self.b.intervals(metric, ray, stepsize)),
+        ] {
+            for interval in intervals {
+                events.push((interval.start, object, true, interval.entry));
+                events.push((interval.end, object, false, interval.exit));
+            }
+        }
+        events.sort_by(|x, y| x.0.total_cmp(&y.0).then(y.2.cmp(&x.2)));
+
+        let mut inside = [false; 2];
+        let mut open = None;
+        let mut intervals = vec![];
+        for (affine, object, entering, mut hit) in events {
+            // The surface of a subtracted object faces into the result
+            if object == 1
+                && self.operation == Operation::Difference
+                && let Some(hit) = &mut hit
+            {
+                hit.normal = SpatialVec(hit.normal.0.map(|c| -c));
+            }
+            inside[object] = entering;
+            let now = self.operation.apply(inside[0], inside[1]);
+            match open {
+                None if now => open = Some((affine, hit)),
+                Some((start, entry)) if !now => {
+                    intervals.push(Interval {
+                        start,
+                        end: affine,
+                        entry,
+                        exit: hit,
+                    });
+                    open = None;
+                }
+                _ => {}
+            }
+        }
+        intervals
+    }
+
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool {
+        match self.operation {
+            Operation::Union => {
+                self.a.in_bounding_box(metric, bbox) || self.b.in_bounding_box(metric, bbox)
+            }
+            Operation::Intersection => {
+                self.a.in_bounding_box(metric, bbox) && self.b.in_bounding_box(metric, bbox)
+            }
+            Operation::Difference => self.a.in_bounding_box(metric, bbox),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::{FourVector, ManifoldFrame},
+        metric::{CarthesianMinkowski, testing::coord},
+        objects::{PlaneCollider, SphereCollider},
+    };
+
+    fn at(x: f64) -> ManifoldFrame<CarthesianMinkowski> {
+        ManifoldFrame::from_four_velocity(
+            &CarthesianMinkowski,
+            coord([0.0, x, 0.0, 0.0]),
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+        )
+    }
+
+    fn sphere(x: f64, radius: f64) -> SphereCollider<CarthesianMinkowski> {
+        SphereCollider {
+            center: at(x),
+            radius,
+            time_thickness: 1e9,
+        }
+    }
+
+    fn plane(x: f64) -> PlaneCollider<CarthesianMinkowski> {
+        PlaneCollider {
+            frame: at(x),
+            normal: SpatialVec([1.0, 0.0, 0.0]),
+            time_thickness: 1e9,
+        }
+    }
+
+    /// Intervals along the x axis, from x = -10 at affine parameter 0, with
+    /// the x components of the entry and exit normals
+    fn along_x(csg: Csg<CarthesianMinkowski>) -> Vec<(f64, f64, f64, f64)> {
+        let mut ray = CarthesianMinkowski::lightray(SpatialVec([1.0, 0.0, 0.0]));
+        ray.root = coord([0.0, -10.0, 0.0, 0.0]);
+        csg.intervals(&CarthesianMinkowski, ray, 30.0)
+            .into_iter()
+            .map(|interval| {
+                let normal = |hit: Option<Hit<_>>| hit.unwrap().normal.0[0];
+                let round = |v: f64| (v * 1e9).round() / 1e9;
+                (
+                    round(interval.start),
+                    round(interval.end),
+                    round(normal(interval.entry)),
+                    round(normal(interval.exit)),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_spheres() {
+        // Inside the first sphere for x in [-2, 2], the second for [0, 4]
+        assert_eq!(
+            along_x(Csg::union(sphere(0.0, 2.0), sphere(2.0, 2.0))),
+            [(8.0, 14.0, -1.0, 1.0)]
+        );
+        assert_eq!(
+            along_x(Csg::intersection(sphere(0.0, 2.0), sphere(2.0, 2.0))),
+            [(10.0, 12.0, -1.0, 1.0)]
+        );
+        // The exit through the subtracted sphere faces into the result
+        assert_eq!(
+            along_x(Csg::difference(sphere(0.0, 2.0), sphere(2.0, 2.0))),
+            [(8.0, 10.0, -1.0, 1.0)]
+        );
+        assert_eq!(
+            along_x(Csg::difference(sphere(2.0, 2.0), sphere(0.0, 2.0))),
+            [(12.0, 14.0, -1.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn touching_spheres_merge() {
+        // The first sphere is left at the same parameter the second is entered
+        assert_eq!(
+            along_x(Csg::union(sphere(0.0, 2.0), sphere(4.0, 2.0))),
+            [(8.0, 16.0, -1.0, 1.0)]
+        );
+        assert_eq!(
+            along_x(Csg::intersection(sphere(0.0, 2.0), sphere(4.0, 2.0))),
+            [(12.0, 12.0, -1.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn surface_operands() {
+        // A plane has no inside, it only adds its surface
+        assert_eq!(
+            along_x(Csg::union(sphere(0.0, 2.0), plane(1.0))),
+            [(8.0, 12.0, -1.0, 1.0)]
+        );
+        assert_eq!(
+            along_x(Csg::union(sphere(0.0, 2.0), plane(5.0))),
+            [(8.0, 12.0, -1.0, 1.0), (15.0, 15.0, -1.0, -1.0)]
+        );
+        assert_eq!(
+            along_x(Csg::intersection(sphere(0.0, 2.0), plane(1.0))),
+            [(11.0, 11.0, -1.0, -1.0)]
+        );
+        assert_eq!(along_x(Csg::intersection(sphere(0.0, 2.0), plane(5.0))), []);
+    }
+}
@@ -0,0 +1,305 @@
+use std::{marker::PhantomData, sync::OnceLock};
+
+use crate::{
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame, ManifoldVector, SpatialVec},
+    metric::Axisymmetric,
+    objects::{Hit, RayIntersector},
+    spectrum::Emission,
+};
+
+/// Angular velocity model for the material of an accretion disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orbits {
+    /// Newtonian angular velocity `sqrt(M / r^3)`, ignoring the spin
+    Keplerian,
+    /// Prograde circular geodesics of the kerr metric
+    #[default]
+    Geodesic,
+}
+
+impl Orbits {
+    /// Angular velocity `dphi/dt` at boyer-lindquist radius r
+    pub fn angular_velocity(self, mass: f64, spin: f64, r: f64) -> f64 {
+        match self {
+            Orbits::Keplerian => (mass / r.powi(3)).sqrt(),
+            Orbits::Geodesic => mass.sqrt() / (r.powf(1.5) + spin * mass.sqrt()),
+        }
+    }
+}
+
+/// Geometrically thin, optically thick accretion disk in the equatorial plane
+/// of an axisymmetric metric.
+///
+/// The disk is a blackbody with the novikov-thorne temperature profile of a
+/// disk whose inner edge exerts no torque. `temperature` is the peak
+/// temperature the disk would have around a non-rotating hole of the same mass
+/// and accretion rate, and `luminance` the luminance there. The disk orbits
+/// towards increasing azimuth. Around holes of positive spin it rotates with
+/// the hole, reaching closer in and running hotter, and around holes of
+/// negative spin against it, staying further out and running colder. Material
+/// inside the innermost stable circular orbit plunges into the hole without
+/// emitting, so a disk reaching further in is dark there. The emission the
+/// disk is added to the scene with is ignored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccretionDisk {
+    pub inner_radius: f64,
+    pub outer_radius: f64,
+    pub orbits: Orbits,
+    pub temperature: f64,
+    pub luminance: f64,
+}
+
+impl AccretionDisk {
+    /// Disk on circular geodesics reaching down to the innermost stable
+    /// circular orbit of the metric
+    pub fn new(metric: &impl Axisymmetric, outer_radius: f64, temperature: f64) -> Self {
+        AccretionDisk {
+            inner_radius: isco(metric),
+            outer_radius,
+            orbits: Orbits::Geodesic,
+            temperature,
+            luminance: 1.0,
+        }
+    }
+
+    /// Temperature and luminance at boyer-lindquist radius r
+    pub fn emission_at(&self, metric: &(impl Axisymmetric + ?Sized), r: f64) -> (f64, f64) {
+        let mass = metric.mass();
+        let inner = self.inner_radius.max(isco(metric));
+        let flux = novikov_thorne_flux(metric.spin() / mass, inner / mass, r / mass)
+            / schwarzschild_peak_flux();
+        (self.temperature * flux.powf(0.25), self.luminance * flux)
+    }
+}
+
+/// Innermost stable circular orbit of the disk material, which orbits towards
+/// increasing azimuth and so against holes with negative spin
+fn isco(metric: &(impl Axisymmetric + ?Sized)) -> f64 {
+    if metric.spin() < 0.0 {
+        metric.retrograde_isco()
+    } else {
+        metric.isco()
+    }
+}
+
+/// Energy, angular momentum and angular velocity of circular geodesics
+/// towards increasing azimuth at radius r, for unit mass. These are prograde
+/// for positive spin a and retrograde for negative spin.
+fn circular_orbit(a: f64, r: f64) -> (f64, f64, f64) {
+    let sr = r.sqrt();
+    let denominator = r.powf(0.75) * (r * sr - 3.0 * sr + 2.0 * a).sqrt();
+    let energy = (r * sr - 2.0 * sr + a) / denominator;
+    let momentum = (r * r - 2.0 * a * sr + a * a) / denominator;
+    (energy, momentum, 1.0 / (r * sr + a))
+}
+
+/// Novikov-thorne flux emitted from each face of the disk at radius r, for
+/// unit mass and accretion rate, leaving out a factor `1 / 4 pi`. The inner
+/// edge must not lie inside the innermost stable circular orbit.
+fn novikov_thorne_flux(a: f64, inner: f64, r: f64) -> f64 {
+    if r <= inner {
+        return 0.0;
+    }
+    let specific = |r: f64| {
+        let (energy, momentum, omega) = circular_orbit(a, r);
+        (energy - omega * momentum, momentum)
+    };
+    let integrand = |r: f64| {
+        let h = 1e-5 * r;
+        let dmomentum = (specific(r + h).1 - specific(r - h).1) / (2.0 * h);
+        specific(r).0 * dmomentum
+    };
+
+    // Simpson's rule
+    const INTERVALS: usize = 64;
+    let width = (r - inner) / INTERVALS as f64;
+    let integral = (0..=INTERVALS)
+        .map(|i| {
+            let weight = match i {
+                0 | INTERVALS => 1.0,
+                i if i % 2 == 1 => 4.0,
+                _ => 2.0,
+            };
+            weight * integrand(inner + i as f64 * width)
+        })
+        .sum::<f64>()
+        * width
+        / 3.0;
+
+    let domega = -1.5 * r.sqrt() / (r.powf(1.5) + a).powi(2);
+    -domega / (r * specific(r).0.powi(2)) * integral
+}
+
+/// Peak of `novikov_thorne_flux` around a non-rotating hole
+fn schwarzschild_peak_flux() -> f64 {
+    static PEAK: OnceLock<f64> = OnceLock::new();
+    *PEAK.get_or_init(|| {
+        // Golden section search, the flux has a single maximum
+        let flux = |r| novikov_thorne_flux(0.0, 6.0, r);
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut low, mut high) = (6.0, 60.0);
+        while high - low > 1e-6 {
+            let left = high - ratio * (high - low);
+            let right = low + ratio * (high - low);
+            if flux(left) < flux(right) {
+                low = left;
+            } else {
+                high = right;
+            }
+        }
+        flux(0.5 * (low + high))
+    })
+}
+
+impl<T: Axisymmetric + ?Sized> RayIntersector<T> for AccretionDisk {
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        // Rays bend strongly close to the hole, so follow the segment to second
+        // order to avoid missing crossings that a straight one would not reach
+        let acceleration = metric.geodesic_acceleration(ray);
+        let along = |affine: f64| Coord {
+            components: ray.root.components
+                + affine * ray.components
+                + (0.5 * affine * affine) * acceleration,
+            _metric: PhantomData,
+        };
+        let [_, _, h0] = metric.equatorial(ray.root);
+        let [_, _, h1] = metric.equatorial(along(stepsize));
+        if h0.signum() == h1.signum() || h0 == h1 {
+            return None;
+        }
+
+        let affine = stepsize * h0 / (h0 - h1);
+        let event = along(affine);
+        let [r, phi, _] = metric.equatorial(event);
+        if !(self.inner_radius..=self.outer_radius).contains(&r) {
+            return None;
+        }
+
+        let omega = self
+            .orbits
+            .angular_velocity(metric.mass(), metric.spin(), r);
+        let velocity = FourVector([1.0, 0.0, 0.0, 0.0]) + omega * metric.rotation(event);
+        let norm = metric.norm(ManifoldVector {
+            root: event,
+            components: velocity,
+        });
+        // Inside the photon orbit no material can move this fast
+        if norm >= 0.0 {
+            return None;
+        }
+        let frame = ManifoldFrame {
+            root: event,
+            axis: [
+                velocity,
+                FourVector([0.0, 1.0, 0.0, 0.0]),
+                FourVector([0.0, 0.0, 1.0, 0.0]),
+                FourVector([0.0, 0.0, 0.0, 1.0]),
+            ],
+        }
+        .normalize(metric);
+        // Direction of the light where it is emitted, at the hit
+        let local = metric.into_local(
+            frame,
+            ManifoldVector {
+                root: event,
+                components: ray.components + affine * acceleration,
+            },
+        );
+
+        Some(Hit {
+            affine,
+            event,
+            position: SpatialVec([r * phi.cos(), r * phi.sin(), 0.0]),
+            normal: SpatialVec([0.0, 0.0, h0.signum()]),
+            direction: local.components,
+            proper_time: event.components.0[0] * (-norm).sqrt(),
+        })
+    }
+
+    fn emission(&self, metric: &T, hit: &Hit<T>) -> Option<Emission> {
+        let [r, _, _] = metric.equatorial(hit.event);
+        let (temperature, luminance) = self.emission_at(metric, r);
+        Some(Emission::Blackbody {
+            temperature,
+            luminance,
+        })
+    }
+
+    /// The disk is not confined to a coordinate range, so this is always true
+    fn in_bounding_box(&self, _metric: &T, _bbox: BoundingBox<T>) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::{Kerr, Schwarzschild};
+
+    #[test]
+    fn no_emission_inside_isco() {
+        let metric = Schwarzschild::new(1.0);
+        let disk = AccretionDisk {
+            inner_radius: 2.5,
+            orbits: Orbits::Keplerian,
+            ..AccretionDisk::new(&metric, 30.0, 1e4)
+        };
+        for i in 0..200 {
+            let r = 2.5 + i as f64 * 0.1;
+            let (temperature, luminance) = disk.emission_at(&metric, r);
+            assert!(
+                temperature.is_finite() && temperature >= 0.0,
+                "{temperature} at {r}"
+            );
+            assert!(
+                luminance.is_finite() && luminance >= 0.0,
+                "{luminance} at {r}"
+            );
+            if r <= isco(&metric) {
+                assert_eq!(luminance, 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn peak_temperature_around_schwarzschild() {
+        let metric = Schwarzschild::new(1.0);
+        let disk = AccretionDisk::new(&metric, 30.0, 1e4);
+        let peak = (0..1000)
+            .map(|i| disk.emission_at(&metric, 6.0 + i as f64 * 0.02).0)
+            .fold(0.0, f64::max);
+        assert!((peak - 1e4).abs() < 1.0, "{peak}");
+    }
+
+    #[test]
+    fn spinning_hole_runs_hotter() {
+        let metric = Kerr::new(1.0, 0.9);
+        let disk = AccretionDisk::new(&metric, 30.0, 1e4);
+        let peak = (0..1000)
+            .map(|i| disk.emission_at(&metric, metric.isco() + i as f64 * 0.02).0)
+            .fold(0.0, f64::max);
+        assert!(peak > 1.2e4, "{peak}");
+    }
+
+    #[test]
+    fn counter_rotating_hole_runs_colder() {
+        let metric = Kerr::new(1.0, -0.9);
+        let disk = AccretionDisk::new(&metric, 30.0, 1e4);
+        assert!((disk.inner_radius - metric.retrograde_isco()).abs() < 1e-12);
+        assert!(disk.inner_radius > 8.0, "{}", disk.inner_radius);
+        let mut peak: f64 = 0.0;
+        for i in 0..1000 {
+            let r = 2.5 + i as f64 * 0.03;
+            let (temperature, luminance) = disk.emission_at(&metric, r);
+            assert!(
+                temperature.is_finite() && luminance.is_finite(),
+                "NaN at {r}"
+            );
+            if r <= disk.inner_radius {
+                assert_eq!(luminance, 0.0);
+            }
+            peak = peak.max(temperature);
+        }
+        assert!(peak > 0.5e4 && peak < 1e4, "{peak}");
+    }
+}
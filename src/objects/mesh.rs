@@ -0,0 +1,606 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    marker::PhantomData,
+    path::Path,
+};
+
+use crate::{
+    geometry::{BoundingBox, Coord, ManifoldFrame, ManifoldVector, SpatialVec},
+    metric::Metric,
+    objects::{Hit, RayIntersector, in_extent, lifetime},
+    util::{cross3, dot3, normalize3, sub3},
+};
+
+/// Rigid triangle mesh, at rest in its local frame.
+///
+/// Vertices are given in the local minkowski coordinates of `frame`, and like
+/// `SphereCollider` the mesh exists for local times between `-time_thickness`
+/// and `time_thickness`. Triangles are kept in a bounding volume hierarchy, so
+/// large meshes only test the triangles near each ray step.
+pub struct Mesh<T: Metric + ?Sized> {
+    pub frame: ManifoldFrame<T>,
+    pub time_thickness: f64,
+    vertices: Vec<SpatialVec>,
+    triangles: Vec<[usize; 3]>,
+    nodes: Vec<Node>,
+}
+
+/// Bounding volume hierarchy node over a range of `Mesh::triangles`
+struct Node {
+    lower: [f64; 3],
+    upper: [f64; 3],
+    /// Child nodes, none for leaves
+    children: Option<[usize; 2]>,
+    start: usize,
+    end: usize,
+}
+
+impl<T: Metric + ?Sized> Mesh<T> {
+    const LEAF_SIZE: usize = 4;
+
+    /// Mesh with the given vertices and triangles of vertex indices, failing
+    /// with `InvalidData` if an index is out of range
+    pub fn new(
+        frame: ManifoldFrame<T>,
+        vertices: Vec<SpatialVec>,
+        triangles: Vec<[usize; 3]>,
+        time_thickness: f64,
+    ) -> io::Result<Self> {
+        if let Some(index) = triangles.iter().flatten().find(|&&i| i >= vertices.len()) {
+            return Err(invalid(format!(
+                "triangle index {index} out of range for {} vertices",
+                vertices.len()
+            )));
+        }
+        let mut mesh = Mesh {
+            frame,
+            time_thickness,
+            vertices,
+            triangles,
+            nodes: vec![],
+        };
+        if !mesh.triangles.is_empty() {
+            mesh.build(0, mesh.triangles.len());
+        }
+        Ok(mesh)
+    }
+
+    /// Load a mesh from an OBJ or PLY file, chosen by the file extension
+    pub fn open(
+        path: impl AsRef<Path>,
+        frame: ManifoldFrame<T>,
+        time_thickness: f64,
+    ) -> io::Result<Self> {
+        let path = path.as_ref();
+        let reader = BufReader::new(File::open(path)?);
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let (vertices, triangles) = match extension.to_ascii_lowercase().as_str() {
+            "obj" => read_obj(reader)?,
+            "ply" => read_ply(reader)?,
+            _ => return Err(invalid(format!("unknown mesh format {extension:?}"))),
+        };
+        Self::new(frame, vertices, triangles, time_thickness)
+    }
+
+    pub fn vertices(&self) -> &[SpatialVec] {
+        &self.vertices
+    }
+
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    fn corners(&self, triangle: [usize; 3]) -> [[f64; 3]; 3] {
+        triangle.map(|i| self.vertices[i].0)
+    }
+
+    /// Build the hierarchy over triangles start..end, splitting at the median
+    /// along the longest axis
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let mut lower = [f64::MAX; 3];
+        let mut upper = [f64::MIN; 3];
+        for &triangle in &self.triangles[start..end] {
+            for corner in self.corners(triangle) {
+                for i in 0..3 {
+                    lower[i] = lower[i].min(corner[i]);
+                    upper[i] = upper[i].max(corner[i]);
+                }
+            }
+        }
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            lower,
+            upper,
+            children: None,
+            start,
+            end,
+        });
+        if end - start <= Self::LEAF_SIZE {
+            return node;
+        }
+
+        let axis = (0..3)
+            .max_by(|&a, &b| (upper[a] - lower[a]).total_cmp(&(upper[b] - lower[b])))
+            .unwrap();
+        let vertices = &self.vertices;
+        let centroid =
+            |triangle: &[usize; 3]| triangle.iter().map(|&i| vertices[i].0[axis]).sum::<f64>();
+        self.triangles[start..end].sort_by(|a, b| centroid(a).total_cmp(&centroid(b)));
+        let middle = (start + end) / 2;
+        let children = [self.build(start, middle), self.build(middle, end)];
+        self.nodes[node].children = Some(children);
+        node
+    }
+
+    /// Nearest triangle crossing of the segment origin + s direction for s in
+    /// lower..upper, as the parameter and the triangle
+    fn nearest(
+        &self,
+        origin: [f64; 3],
+        direction: [f64; 3],
+        lower: f64,
+        upper: f64,
+    ) -> Option<(f64, [usize; 3])> {
+        let mut nearest: Option<(f64, [usize; 3])> = None;
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            let limit = nearest.map_or(upper, |(s, _)| s);
+            if !slab(node.lower, node.upper, origin, direction, lower, limit) {
+                continue;
+            }
+            match node.children {
+                Some(children) => stack.extend(children),
+                None => {
+                    for &triangle in &self.triangles[node.start..node.end] {
+                        let limit = nearest.map_or(upper, |(s, _)| s);
+                        let crossing = crossing(self.corners(triangle), origin, direction);
+                        if let Some(s) = crossing.filter(|s| (lower..=limit).contains(s)) {
+                            nearest = Some((s, triangle));
+                        }
+                    }
+                }
+            }
+        }
+        nearest
+    }
+}
+
+/// Whether the segment origin + s direction for s in lower..upper passes
+/// through the axis aligned box
+fn slab(
+    lower: [f64; 3],
+    upper: [f64; 3],
+    origin: [f64; 3],
+    direction: [f64; 3],
+    mut from: f64,
+    mut to: f64,
+) -> bool {
+    for i in 0..3 {
+        let t1 = (lower[i] - origin[i]) / direction[i];
+        let t2 = (upper[i] - origin[i]) / direction[i];
+        if t1.is_nan() || t2.is_nan() {
+            // Parallel to the slab, and on one of its planes
+            continue;
+        }
+        from = from.max(t1.min(t2));
+        to = to.min(t1.max(t2));
+    }
+    from <= to
+}
+
+/// Parameter at which the line origin + s direction crosses the triangle, by
+/// the moller-trumbore algorithm
+fn crossing(corners: [[f64; 3]; 3], origin: [f64; 3], direction: [f64; 3]) -> Option<f64> {
+    let e1 = sub3(corners[1], corners[0]);
+    let e2 = sub3(corners[2], corners[0]);
+    let p = cross3(direction, e2);
+    let det = dot3(e1, p);
+    if det.abs() < 1e-14 {
+        return None;
+    }
+    let s = sub3(origin, corners[0]);
+    let u = dot3(s, p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = cross3(s, e1);
+    let v = dot3(direction, q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    Some(dot3(e2, q) / det)
+}
+
+impl<T: Metric + ?Sized> RayIntersector<T> for Mesh<T> {
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let local_ray = metric.into_local(self.frame, ray);
+        let (lower, upper) = lifetime(local_ray, stepsize, self.time_thickness);
+        if lower > upper {
+            return None;
+        }
+
+        let [_, ox, oy, oz] = local_ray.root.components.0;
+        let [_, dx, dy, dz] = local_ray.components.0;
+        let (affine, triangle) = self.nearest([ox, oy, oz], [dx, dy, dz], lower, upper)?;
+
+        let corners = self.corners(triangle);
+        let mut normal = normalize3(cross3(
+            sub3(corners[1], corners[0]),
+            sub3(corners[2], corners[0]),
+        ));
+        // Face the side the ray comes from
+        if dot3(normal, [dx, dy, dz]) > 0.0 {
+            normal = normal.map(|c| -c);
+        }
+        let local = local_ray.root.components + affine * local_ray.components;
+
+        Some(Hit {
+            affine,
+            event: Coord {
+                components: ray.root.components + affine * ray.components,
+                _metric: PhantomData,
+            },
+            position: SpatialVec([local.0[1], local.0[2], local.0[3]]),
+            normal: SpatialVec(normal),
+            direction: local_ray.components,
+            proper_time: local.0[0],
+        })
+    }
+
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool {
+        let Some(root) = self.nodes.first() else {
+            return false;
+        };
+        // Extent about the frame origin covering the root of the hierarchy
+        let extent = std::array::from_fn(|i| root.lower[i].abs().max(root.upper[i].abs()));
+        in_extent(metric, self.frame, bbox, extent, self.time_thickness)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Count or index read from a PLY list, which must be a non-negative integer
+fn ply_index(value: f64) -> io::Result<usize> {
+    if value >= 0.0 && value.fract() == 0.0 && value <= u32::MAX as f64 {
+        Ok(value as usize)
+    } else {
+        Err(invalid(format!("bad PLY index {value}")))
+    }
+}
+
+/// Split a polygon into a fan of triangles
+fn triangulate(polygon: &[usize], triangles: &mut Vec<[usize; 3]>) {
+    for i in 1..polygon.len().saturating_sub(1) {
+        triangles.push([polygon[0], polygon[i], polygon[i + 1]]);
+    }
+}
+
+/// Read vertices and triangles from a wavefront OBJ file. Only vertex
+/// positions and faces are used, polygons are split into triangles.
+pub fn read_obj(reader: impl BufRead) -> io::Result<(Vec<SpatialVec>, Vec<[usize; 3]>)> {
+    let mut vertices = vec![];
+    let mut triangles = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let mut position = [0.0; 3];
+                for c in &mut position {
+                    *c = tokens
+                        .next()
+                        .and_then(|t| t.parse().ok())
+                        .ok_or_else(|| invalid(format!("bad vertex {line:?}")))?;
+                }
+                vertices.push(SpatialVec(position));
+            }
+            Some("f") => {
+                let polygon = tokens
+                    .map(|token| {
+                        // Indices are 1 based, negative ones count from the end
+                        let index: i64 = token
+                            .split('/')
+                            .next()
+                            .and_then(|i| i.parse().ok())
+                            .ok_or_else(|| invalid(format!("bad face {line:?}")))?;
+                        let index = if index < 0 {
+                            vertices.len() as i64 + index
+                        } else {
+                            index - 1
+                        };
+                        usize::try_from(index)
+                            .ok()
+                            .filter(|&i| i < vertices.len())
+                            .ok_or_else(|| invalid(format!("face index out of range {line:?}")))
+                    })
+                    .collect::<io::Result<Vec<_>>>()?;
+                triangulate(&polygon, &mut triangles);
+            }
+            _ => {}
+        }
+    }
+    Ok((vertices, triangles))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PlyFormat {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+#[derive(Clone, Copy)]
+enum PlyScalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl PlyScalar {
+    fn parse(name: &str) -> io::Result<Self> {
+        Ok(match name {
+            "char" | "int8" => PlyScalar::I8,
+            "uchar" | "uint8" => PlyScalar::U8,
+            "short" | "int16" => PlyScalar::I16,
+            "ushort" | "uint16" => PlyScalar::U16,
+            "int" | "int32" => PlyScalar::I32,
+            "uint" | "uint32" => PlyScalar::U32,
+            "float" | "float32" => PlyScalar::F32,
+            "double" | "float64" => PlyScalar::F64,
+            _ => return Err(invalid(format!("unknown PLY type {name:?}"))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyScalar::I8 | PlyScalar::U8 => 1,
+            PlyScalar::I16 | PlyScalar::U16 => 2,
+            PlyScalar::I32 | PlyScalar::U32 | PlyScalar::F32 => 4,
+            PlyScalar::F64 => 8,
+        }
+    }
+
+    fn read(self, reader: &mut impl Read, format: PlyFormat) -> io::Result<f64> {
+        let mut bytes = [0; 8];
+        let bytes = &mut bytes[..self.size()];
+        reader.read_exact(bytes)?;
+        if format == PlyFormat::BigEndian {
+            bytes.reverse();
+        }
+        // Bytes are now little endian
+        let bytes: &[u8] = bytes;
+        Ok(match self {
+            PlyScalar::I8 => bytes[0] as i8 as f64,
+            PlyScalar::U8 => bytes[0] as f64,
+            PlyScalar::I16 => i16::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyScalar::U16 => u16::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyScalar::I32 => i32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyScalar::U32 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyScalar::F32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyScalar::F64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+        })
+    }
+}
+
+enum PlyProperty {
+    Scalar(String, PlyScalar),
+    List(String, PlyScalar, PlyScalar),
+}
+
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+/// Read vertices and triangles from a PLY file, in ascii or binary format.
+/// Only the vertex `x`, `y` and `z` properties and the face `vertex_indices`
+/// (or `vertex_index`) lists are used, polygons are split into triangles.
+pub fn read_ply(mut reader: impl BufRead) -> io::Result<(Vec<SpatialVec>, Vec<[usize; 3]>)> {
+    let mut line = String::new();
+    let mut header = |reader: &mut dyn BufRead| -> io::Result<String> {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(invalid("unexpected end of PLY header"));
+        }
+        Ok(line.trim().to_string())
+    };
+
+    if header(&mut reader)? != "ply" {
+        return Err(invalid("not a PLY file"));
+    }
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = vec![];
+    loop {
+        let line = header(&mut reader)?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["format", name, _] => {
+                format = Some(match *name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::LittleEndian,
+                    "binary_big_endian" => PlyFormat::BigEndian,
+                    _ => return Err(invalid(format!("unknown PLY format {name:?}"))),
+                })
+            }
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| invalid(format!("bad element {line:?}")))?,
+                properties: vec![],
+            }),
+            ["property", "list", count, item, name] => elements
+                .last_mut()
+                .ok_or_else(|| invalid("property outside element"))?
+                .properties
+                .push(PlyProperty::List(
+                    name.to_string(),
+                    PlyScalar::parse(count)?,
+                    PlyScalar::parse(item)?,
+                )),
+            ["property", kind, name] => elements
+                .last_mut()
+                .ok_or_else(|| invalid("property outside element"))?
+                .properties
+                .push(PlyProperty::Scalar(
+                    name.to_string(),
+                    PlyScalar::parse(kind)?,
+                )),
+            ["end_header"] => break,
+            _ => {}
+        }
+    }
+    let format = format.ok_or_else(|| invalid("PLY file without format"))?;
+
+    // Ascii bodies are read as a stream of numbers, binary ones value by value
+    let mut numbers = vec![];
+    if format == PlyFormat::Ascii {
+        let mut body = String::new();
+        reader.read_to_string(&mut body)?;
+        numbers = body
+            .split_whitespace()
+            .map(|t| {
+                t.parse::<f64>()
+                    .map_err(|_| invalid(format!("bad number {t:?}")))
+            })
+            .collect::<io::Result<_>>()?;
+        numbers.reverse();
+    }
+    let mut value = |reader: &mut dyn Read, kind: PlyScalar| -> io::Result<f64> {
+        if format == PlyFormat::Ascii {
+            numbers
+                .pop()
+                .ok_or_else(|| invalid("unexpected end of PLY data"))
+        } else {
+            kind.read(&mut &mut *reader, format)
+        }
+    };
+
+    let mut vertices = vec![];
+    let mut triangles = vec![];
+    for element in &elements {
+        for _ in 0..element.count {
+            let mut position = [0.0; 3];
+            for property in &element.properties {
+                match property {
+                    PlyProperty::Scalar(name, kind) => {
+                        let v = value(&mut reader, *kind)?;
+                        if element.name == "vertex" {
+                            match name.as_str() {
+                                "x" => position[0] = v,
+                                "y" => position[1] = v,
+                                "z" => position[2] = v,
+                                _ => {}
+                            }
+                        }
+                    }
+                    PlyProperty::List(name, count, item) => {
+                        let count = ply_index(value(&mut reader, *count)?)?;
+                        let items = (0..count)
+                            .map(|_| value(&mut reader, *item))
+                            .collect::<io::Result<Vec<_>>>()?;
+                        let indices = name == "vertex_indices" || name == "vertex_index";
+                        if element.name == "face" && indices {
+                            let polygon = items
+                                .into_iter()
+                                .map(ply_index)
+                                .collect::<io::Result<Vec<_>>>()?;
+                            triangulate(&polygon, &mut triangles);
+                        }
+                    }
+                }
+            }
+            if element.name == "vertex" {
+                vertices.push(SpatialVec(position));
+            }
+        }
+    }
+    if triangles.iter().flatten().any(|&i| i >= vertices.len()) {
+        return Err(invalid("PLY face index out of range"));
+    }
+    Ok((vertices, triangles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{geometry::FourVector, metric::CarthesianMinkowski};
+
+    fn frame() -> ManifoldFrame<CarthesianMinkowski> {
+        ManifoldFrame::from_four_velocity(
+            &CarthesianMinkowski,
+            Coord {
+                components: FourVector::default(),
+                _metric: PhantomData,
+            },
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+        )
+    }
+
+    fn triangle() -> Vec<SpatialVec> {
+        vec![
+            SpatialVec([0.0, 0.0, 0.0]),
+            SpatialVec([1.0, 0.0, 0.0]),
+            SpatialVec([0.0, 1.0, 0.0]),
+        ]
+    }
+
+    #[test]
+    fn new_rejects_out_of_range_index() {
+        assert!(Mesh::new(frame(), triangle(), vec![[0, 1, 2]], 1.0).is_ok());
+        let error = Mesh::new(frame(), triangle(), vec![[0, 1, 3]], 1.0).err();
+        assert_eq!(error.map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn obj_polygons_are_triangulated() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 -1\n";
+        let (vertices, triangles) = read_obj(obj.as_bytes()).unwrap();
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(triangles, vec![[0, 1, 2], [0, 2, 3]]);
+        assert!(read_obj("v 0 0 0\nf 1 2 3\n".as_bytes()).is_err());
+    }
+
+    const PLY_HEADER: &str = "ply\nformat ascii 1.0\nelement vertex 3\n\
+        property float x\nproperty float y\nproperty float z\n\
+        element face 1\nproperty list uchar int vertex_indices\nend_header\n";
+
+    #[test]
+    fn ply_reads_faces() {
+        let ply = format!("{PLY_HEADER}0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n");
+        let (vertices, triangles) = read_ply(ply.as_bytes()).unwrap();
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(triangles, vec![[0, 1, 2]]);
+
+        // Other lists, such as texture coordinates, may hold any numbers
+        let header = PLY_HEADER.replace(
+            "end_header",
+            "property list uchar float texcoord\nend_header",
+        );
+        let ply = format!("{header}0 0 0\n1 0 0\n0 1 0\n3 0 1 2 6 0.1 0.2 -0.5 0.4 0.9 1.5\n");
+        let (vertices, triangles) = read_ply(ply.as_bytes()).unwrap();
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(triangles, vec![[0, 1, 2]]);
+    }
+
+    #[test]
+    fn ply_rejects_bad_indices() {
+        for face in ["3 0 -1 2", "3 0 1 3", "3 0 1.5 2"] {
+            let ply = format!("{PLY_HEADER}0 0 0\n1 0 0\n0 1 0\n{face}\n");
+            assert!(read_ply(ply.as_bytes()).is_err(), "{face}");
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use std::marker::PhantomData;
+
+use crate::{
+    geometry::{BoundingBox, Coord, ManifoldFrame, ManifoldVector, SpatialVec},
+    metric::{CarthesianMinkowski, Metric},
+    objects::{Hit, Interval, RayIntersector, in_extent, lifetime, local_box},
+    util::{dot3, normalize3},
+};
+
+/// Surface crossing along a segment, as affine parameter and outward normal
+pub(crate) type Crossing = (f64, [f64; 3]);
+
+/// Split a local ray into spatial origin and direction
+pub(crate) fn spatial(local_ray: ManifoldVector<CarthesianMinkowski>) -> ([f64; 3], [f64; 3]) {
+    let [_, ox, oy, oz] = local_ray.root.components.0;
+    let [_, dx, dy, dz] = local_ray.components.0;
+    ([ox, oy, oz], [dx, dy, dz])
+}
+
+/// Hit at a crossing of a local ray
+fn hit_at<T: Metric + ?Sized>(
+    ray: ManifoldVector<T>,
+    local_ray: ManifoldVector<CarthesianMinkowski>,
+    (affine, normal): Crossing,
+) -> Hit<T> {
+    let local = local_ray.root.components + affine * local_ray.components;
+    Hit {
+        affine,
+        event: Coord {
+            components: ray.root.components + affine * ray.components,
+            _metric: PhantomData,
+        },
+        position: SpatialVec([local.0[1], local.0[2], local.0[3]]),
+        normal: SpatialVec(normal),
+        direction: local_ray.components,
+        proper_time: local.0[0],
+    }
+}
+
+/// Hit at the first crossing within the lifetime of an object, if any
+fn first_hit<T: Metric + ?Sized>(
+    ray: ManifoldVector<T>,
+    local_ray: ManifoldVector<CarthesianMinkowski>,
+    (lower, upper): (f64, f64),
+    crossings: impl IntoIterator<Item = Crossing>,
+) -> Option<Hit<T>> {
+    let crossing = crossings
+        .into_iter()
+        .filter(|(s, _)| (lower..=upper).contains(s))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))?;
+    Some(hit_at(ray, local_ray, crossing))
+}
+
+/// Intervals inside a solid between lower and upper, from its crossings and
+/// whether the ray starts inside. Crossings are entries or exits depending on
+/// the direction of their outward normal.
+fn solid_intervals<T: Metric + ?Sized>(
+    ray: ManifoldVector<T>,
+    local_ray: ManifoldVector<CarthesianMinkowski>,
+    (lower, upper): (f64, f64),
+    mut crossings: Vec<Crossing>,
+    inside: bool,
+) -> Vec<Interval<T>> {
+    let mut intervals = vec![];
+    if lower > upper {
+        return intervals;
+    }
+    crossings.retain(|(s, _)| (lower..=upper).contains(s));
+    crossings.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    let (_, direction) = spatial(local_ray);
+
+    let mut open = inside.then_some((lower, None));
+    for crossing in crossings {
+        let entering = dot3(crossing.1, direction) < 0.0;
+        match open {
+            None if entering => open = Some((crossing.0, Some(hit_at(ray, local_ray, crossing)))),
+            Some((start, entry)) if !entering => {
+                intervals.push(Interval {
+                    start,
+                    end: crossing.0,
+                    entry,
+                    exit: Some(hit_at(ray, local_ray, crossing)),
+                });
+                open = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((start, entry)) = open {
+        intervals.push(Interval {
+            start,
+            end: upper,
+            entry,
+            exit: None,
+        });
+    }
+    intervals
+}
+
+/// Intervals inside a convex solid between lower and upper, from its entry
+/// and exit along the whole line of the ray
+pub(crate) fn convex_intervals<T: Metric + ?Sized>(
+    ray: ManifoldVector<T>,
+    local_ray: ManifoldVector<CarthesianMinkowski>,
+    window: (f64, f64),
+    crossings: Vec<Crossing>,
+) -> Vec<Interval<T>> {
+    let inside =
+        matches!(crossings[..], [(enter, _), (exit, _)] if enter < window.0 && window.0 < exit);
+    solid_intervals(ray, local_ray, window, crossings, inside)
+}
+
+/// Normal facing against the direction of the ray, for surfaces without an
+/// inside
+fn facing(normal: [f64; 3], direction: [f64; 3]) -> [f64; 3] {
+    if dot3(normal, direction) > 0.0 {
+        normal.map(|c| -c)
+    } else {
+        normal
+    }
+}
+
+/// Infinite plane through the origin of its frame
+pub struct PlaneCollider<T: Metric + ?Sized> {
+    pub frame: ManifoldFrame<T>,
+    /// Normal in the local frame
+    pub normal: SpatialVec,
+    pub time_thickness: f64,
+}
+
+impl<T: Metric + ?Sized> PlaneCollider<T> {
+    pub(crate) fn crossings(&self, origin: [f64; 3], direction: [f64; 3]) -> Vec<Crossing> {
+        let normal = normalize3(self.normal.0);
+        let rate = dot3(normal, direction);
+        if rate == 0.0 {
+            return vec![];
+        }
+        vec![(-dot3(normal, origin) / rate, facing(normal, direction))]
+    }
+}
+
+impl<T: Metric + ?Sized> RayIntersector<T> for PlaneCollider<T> {
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        let local_ray = metric.into_local(self.frame, ray);
+        let (origin, direction) = spatial(local_ray);
+        let window = lifetime(local_ray, stepsize, self.time_thickness);
+        first_hit(ray, local_ray, window, self.crossings(origin, direction))
+    }
+
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool {
+        let (lower, upper) = local_box(metric, self.frame, bbox);
+        // The plane passes through the box if its corners are not all on one
+        // side, with some margin for the first order into_local
+        let normal = normalize3(self.normal.0);
+        let distances = (0..8).map(|corner| {
+            let point: [f64; 3] = std::array::from_fn(|i| {
+                if (corner >> i) & 1 == 0 {
+                    lower[i + 1]
+                } else {
+                    upper[i + 1]
+                }
+            });
+            dot3(normal, point)
+        });
+        let (low, high) = distances.fold((f64::MAX, f64::MIN), |(low, high), d| {
+            (low.min(d), high.max(d))
+        });
+        let margin = 0.1 * (high - low);
+        lower[0] < 2.0 * self.time_thickness
+            && upper[0] > -2.0 * self.time_thickness
+            && low - margin <= 0.0
+            && high + margin >= 0.0
+    }
+}
+
+/// Box centered on the origin of its frame, with faces aligned to the local
+/// axes
+pub struct BoxCollider<T: Metric + ?Sized> {
+    pub frame: ManifoldFrame<T>,
+    /// Half the edge lengths along the local axes
+    pub half_size: SpatialVec,
+    pub time_thickness: f64,
+}
+
+impl<T: Metric + ?Sized> BoxCollider<T> {
+    pub(crate) fn crossings(&self, origin: [f64; 3], direction: [f64; 3]) -> Vec<Crossing> {
+        let mut enter = (f64::NEG_INFINITY, [0.0; 3]);
+        let mut exit = (f64::INFINITY, [0.0; 3]);
+        for i in 0..3 {
+            let half = self.half_size.0[i];
+            if direction[i] == 0.0 {
+                if origin[i].abs() > half {
+                    return vec![];
+                }
+                continue;
+            }
+            let t1 = (-half - origin[i]) / direction[i];
+            let t2 = (half - origin[i]) / direction[i];
+            let mut normal = [0.0; 3];
+            normal[i] = direction[i].signum();
+            if t1.min(t2) > enter.0 {
+                enter = (t1.min(t2), normal.map(|c| -c));
+            }
+            if t1.max(t2) < exit.0 {
+                exit = (t1.max(t2), normal);
+            }
+        }
+        if enter.0 > exit.0 {
+            return vec![];
+        }
+        vec![enter, exit]
+    }
+}
+
+impl<T: Metric + ?Sized> RayIntersector<T> for BoxCollider<T> {
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        let local_ray = metric.into_local(self.frame, ray);
+        let (origin, direction) = spatial(local_ray);
+        let window = lifetime(local_ray, stepsize, self.time_thickness);
+        first_hit(ray, local_ray, window, self.crossings(origin, direction))
+    }
+
+    fn intervals(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Vec<Interval<T>> {
+        let local_ray = metric.into_local(self.frame, ray);
+        let (origin, direction) = spatial(local_ray);
+        let window = lifetime(local_ray, stepsize, self.time_thickness);
+        convex_intervals(ray, local_ray, window, self.crossings(origin, direction))
+    }
+
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool {
+        in_extent(
+            metric,
+            self.frame,
+            bbox,
+            self.half_size.0,
+            self.time_thickness,
+        )
+    }
+}
+
+/// Closed cylinder centered on the origin of its frame, around the local z
+/// axis
+pub struct CylinderCollider<T: Metric + ?Sized> {
+    pub frame: ManifoldFrame<T>,
+    pub radius: f64,
+    /// Half the length along the axis
+    pub half_height: f64,
+    pub time_thickness: f64,
+}
+
+impl<T: Metric + ?Sized> CylinderCollider<T> {
+    pub(crate) fn crossings(&self, origin: [f64; 3], direction: [f64; 3]) -> Vec<Crossing> {
+        let [ox, oy, oz] = origin;
+        let [dx, dy, dz] = direction;
+
+        // Interval inside the infinite cylinder
+        let a = dx * dx + dy * dy;
+        let b = ox * dx + oy * dy;
+        let c = ox * ox + oy * oy - self.radius * self.radius;
+        let (side_in, side_out) = if a == 0.0 {
+            if c > 0.0 {
+                return vec![];
+            }
+            (f64::NEG_INFINITY, f64::INFINITY)
+        } else {
+            let d = b * b - a * c;
+            if d < 0.0 {
+                return vec![];
+            }
+            ((-b - d.sqrt()) / a, (-b + d.sqrt()) / a)
+        };
+
+        // Interval between the caps
+        let (cap_in, cap_out) = if dz == 0.0 {
+            if oz.abs() > self.half_height {
+                return vec![];
+            }
+            (f64::NEG_INFINITY, f64::INFINITY)
+        } else {
+            let t1 = (-self.half_height - oz) / dz;
+            let t2 = (self.half_height - oz) / dz;
+            (t1.min(t2), t1.max(t2))
+        };
+
+        let side_normal = |s: f64| normalize3([ox + s * dx, oy + s * dy, 0.0]);
+        let enter = if side_in > cap_in {
+            (side_in, side_normal(side_in))
+        } else {
+            (cap_in, [0.0, 0.0, -dz.signum()])
+        };
+        let exit = if side_out < cap_out {
+            (side_out, side_normal(side_out))
+        } else {
+            (cap_out, [0.0, 0.0, dz.signum()])
+        };
+        if enter.0 > exit.0 {
+            return vec![];
+        }
+        vec![enter, exit]
+    }
+}
+
+impl<T: Metric + ?Sized> RayIntersector<T> for CylinderCollider<T> {
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        let local_ray = metric.into_local(self.frame, ray);
+        let (origin, direction) = spatial(local_ray);
+        let window = lifetime(local_ray, stepsize, self.time_thickness);
+        first_hit(ray, local_ray, window, self.crossings(origin, direction))
+    }
+
+    fn intervals(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Vec<Interval<T>> {
+        let local_ray = metric.into_local(self.frame, ray);
+        let (origin, direction) = spatial(local_ray);
+        let window = lifetime(local_ray, stepsize, self.time_thickness);
+        convex_intervals(ray, local_ray, window, self.crossings(origin, direction))
+    }
+
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool {
+        let extent = [self.radius, self.radius, self.half_height];
+        in_extent(metric, self.frame, bbox, extent, self.time_thickness)
+    }
+}
+
+/// Torus centered on the origin of its frame, around the local z axis
+pub struct TorusCollider<T: Metric + ?Sized> {
+    pub frame: ManifoldFrame<T>,
+    /// Distance from the center to the middle of the tube
+    pub major_radius: f64,
+    /// Radius of the tube
+    pub minor_radius: f64,
+    pub time_thickness: f64,
+}
+
+impl<T: Metric + ?Sized> TorusCollider<T> {
+    /// Samples per segment when searching for crossings
+    const SAMPLES: usize = 32;
+
+    /// Implicit function of the torus, negative inside
+    fn implicit(&self, p: [f64; 3]) -> f64 {
+        let (big, small) = (self.major_radius, self.minor_radius);
+        let k = dot3(p, p) + big * big - small * small;
+        k * k - 4.0 * big * big * (p[0] * p[0] + p[1] * p[1])
+    }
+
+    /// Crossings for parameters between lower and upper. The surface is a
+    /// quartic, whose roots are bracketed by sampling and refined by
+    /// bisection, so a ray grazing the tube may be missed.
+    pub(crate) fn crossings(
+        &self,
+        origin: [f64; 3],
+        direction: [f64; 3],
+        lower: f64,
+        upper: f64,
+    ) -> Vec<Crossing> {
+        let at = |s: f64| std::array::from_fn(|i| origin[i] + s * direction[i]);
+        let f = |s: f64| self.implicit(at(s));
+        let mut crossings = vec![];
+        if lower >= upper || lower.is_nan() || upper.is_nan() {
+            return crossings;
+        }
+        let width = (upper - lower) / Self::SAMPLES as f64;
+        let mut previous = (lower, f(lower));
+        for i in 1..=Self::SAMPLES {
+            let s = lower + i as f64 * width;
+            let value = f(s);
+            if value.signum() != previous.1.signum() {
+                let (mut a, mut b) = (previous.0, s);
+                for _ in 0..50 {
+                    let middle = 0.5 * (a + b);
+                    if f(middle).signum() == previous.1.signum() {
+                        a = middle;
+                    } else {
+                        b = middle;
+                    }
+                }
+                let s = 0.5 * (a + b);
+                let p: [f64; 3] = at(s);
+                let ring = normalize3([p[0], p[1], 0.0]).map(|c| c * self.major_radius);
+                let normal = normalize3([p[0] - ring[0], p[1] - ring[1], p[2]]);
+                crossings.push((s, normal));
+            }
+            previous = (s, value);
+        }
+        crossings
+    }
+}
+
+impl<T: Metric + ?Sized> RayIntersector<T> for TorusCollider<T> {
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        let local_ray = metric.into_local(self.frame, ray);
+        let (origin, direction) = spatial(local_ray);
+        let (lower, upper) = lifetime(local_ray, stepsize, self.time_thickness);
+        let crossings = self.crossings(origin, direction, lower, upper);
+        first_hit(ray, local_ray, (lower, upper), crossings)
+    }
+
+    fn intervals(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Vec<Interval<T>> {
+        let local_ray = metric.into_local(self.frame, ray);
+        let (origin, direction) = spatial(local_ray);
+        let (lower, upper) = lifetime(local_ray, stepsize, self.time_thickness);
+        let start: [f64; 3] = std::array::from_fn(|i| origin[i] + lower * direction[i]);
+        let inside = self.implicit(start) < 0.0;
+        let crossings = self.crossings(origin, direction, lower, upper);
+        solid_intervals(ray, local_ray, (lower, upper), crossings, inside)
+    }
+
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool {
+        let outer = self.major_radius + self.minor_radius;
+        let extent = [outer, outer, self.minor_radius];
+        in_extent(metric, self.frame, bbox, extent, self.time_thickness)
+    }
+}
+
+/// Flat disc or annulus in the local xy plane, centered on the origin of its
+/// frame
+pub struct DiscCollider<T: Metric + ?Sized> {
+    pub frame: ManifoldFrame<T>,
+    /// Radius of the hole in the middle, zero for a full disc
+    pub inner_radius: f64,
+    pub outer_radius: f64,
+    pub time_thickness: f64,
+}
+
+impl<T: Metric + ?Sized> DiscCollider<T> {
+    pub(crate) fn crossings(&self, origin: [f64; 3], direction: [f64; 3]) -> Vec<Crossing> {
+        if direction[2] == 0.0 {
+            return vec![];
+        }
+        let s = -origin[2] / direction[2];
+        let (x, y) = (origin[0] + s * direction[0], origin[1] + s * direction[1]);
+        let r = (x * x + y * y).sqrt();
+        if r < self.inner_radius || r > self.outer_radius {
+            return vec![];
+        }
+        vec![(s, facing([0.0, 0.0, 1.0], direction))]
+    }
+}
+
+impl<T: Metric + ?Sized> RayIntersector<T> for DiscCollider<T> {
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        let local_ray = metric.into_local(self.frame, ray);
+        let (origin, direction) = spatial(local_ray);
+        let window = lifetime(local_ray, stepsize, self.time_thickness);
+        first_hit(ray, local_ray, window, self.crossings(origin, direction))
+    }
+
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool {
+        let extent = [self.outer_radius, self.outer_radius, 0.0];
+        in_extent(metric, self.frame, bbox, extent, self.time_thickness)
+    }
+}
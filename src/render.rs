@@ -1,25 +1,510 @@
-use image::{Rgb, RgbImage};
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
 
-use crate::{camera::Camera, geometry::BoundingBox, metric::Metric, objects::RayIntersector};
+use image::{Rgb, Rgb32FImage, RgbImage};
 
+use crate::{
+    background::{Background, Uniform},
+    broadphase::BroadPhase,
+    camera::Camera,
+    color::{frequency_shift, to_rgb},
+    geometry::{BoundingBox, FourVector, ManifoldVector},
+    integrator::{Integrator, Native},
+    medium::{Medium, static_velocity},
+    metric::Metric,
+    objects::RayIntersector,
+    spectrum::Emission,
+};
+
+/// How the observed intensity scales with the frequency ratio g between
+/// observer and emitter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Beaming {
+    /// Keep the emitted intensity
+    #[default]
+    Off,
+    /// Specific intensity, scaling as g^3 by the invariance of I_nu / nu^3
+    Specific,
+    /// Frequency integrated intensity, scaling as g^4
+    Bolometric,
+}
+
+impl Beaming {
+    pub fn factor(self, g: f64) -> f64 {
+        match self {
+            Beaming::Off => 1.0,
+            Beaming::Specific => g.powi(3),
+            Beaming::Bolometric => g.powi(4),
+        }
+    }
+}
+
+/// Why tracing a ray stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Termination {
+    /// Left the bounding box, showing the background
+    Escaped,
+    /// Fell behind a horizon, see `Metric::captured`
+    Captured,
+    /// Hit an object
+    Hit,
+    /// Passed through media that absorbed nearly all light behind them
+    Absorbed,
+    /// Took `RenderSettings::max_steps` steps without any of the above
+    StepBudgetExhausted,
+}
+
+impl Termination {
+    /// Color representing the termination reason in diagnostic images
+    pub fn color(self) -> Rgb<u8> {
+        match self {
+            Termination::Escaped => Rgb([0, 0, 255]),
+            Termination::Captured => Rgb([0, 0, 0]),
+            Termination::Hit => Rgb([0, 255, 0]),
+            Termination::Absorbed => Rgb([255, 255, 0]),
+            Termination::StepBudgetExhausted => Rgb([255, 0, 0]),
+        }
+    }
+}
+
+/// Settings controlling how rays are traced through a scene
+pub struct RenderSettings<T: Metric + ?Sized> {
+    /// Scheme used to follow rays along their geodesics
+    pub integrator: Box<dyn Integrator<T>>,
+    /// Affine parameter step size, the initial one for adaptive integrators
+    pub step: f64,
+    /// Shift object spectra by the frequency ratio between emitter and camera,
+    /// for cameras sending null rays
+    pub doppler: bool,
+    /// Scale object intensities for relativistic beaming
+    pub beaming: Beaming,
+    /// Number of integration steps after which a ray is given up, as it may
+    /// be trapped, for instance near a photon sphere
+    pub max_steps: usize,
+    /// Seen by rays leaving the bounding box
+    pub background: Box<dyn Background>,
+    /// Emitting and absorbing media filling the scene
+    pub media: Vec<Box<dyn Medium<T>>>,
+    /// Number of threads rendering scanlines in parallel
+    pub threads: usize,
+}
+
+impl<T: Metric + ?Sized> RenderSettings<T> {
+    /// Settings using the metric's own fixed size geodesic step
+    pub fn new(step: f64) -> Self {
+        RenderSettings {
+            integrator: Box::new(Native),
+            step,
+            doppler: true,
+            beaming: Beaming::Off,
+            max_steps: 100_000,
+            background: Box::new(Uniform::default()),
+            media: vec![],
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+
+    pub fn with_integrator(self, integrator: impl Integrator<T> + 'static) -> Self {
+        RenderSettings {
+            integrator: Box::new(integrator),
+            ..self
+        }
+    }
+
+    pub fn with_max_steps(self, max_steps: usize) -> Self {
+        RenderSettings { max_steps, ..self }
+    }
+
+    pub fn with_background(self, background: impl Background + 'static) -> Self {
+        RenderSettings {
+            background: Box::new(background),
+            ..self
+        }
+    }
+
+    pub fn with_medium(mut self, medium: impl Medium<T> + 'static) -> Self {
+        self.media.push(Box::new(medium));
+        self
+    }
+
+    pub fn with_threads(self, threads: usize) -> Self {
+        RenderSettings { threads, ..self }
+    }
+}
+
+/// Render a scene to an 8 bit sRGB image, clipping intensities above 1.
+///
+/// Scanlines are rendered on `settings.threads` threads, which share the
+/// metric, camera, objects and settings. That is why the metric, camera,
+/// integrator, object, texture, background and medium traits all require
+/// `Sync`.
 pub fn render_scene<T: Metric + ?Sized>(
+    metric: &T,
+    camera: impl Camera<T>,
+    objects: Vec<(Emission, Box<dyn RayIntersector<T>>)>,
+    bounds: BoundingBox<T>,
+    settings: RenderSettings<T>,
+) -> RgbImage {
+    to_ldr(&render_scene_hdr(metric, camera, objects, bounds, settings))
+}
+
+/// Render a scene to linear floating point intensities, which can exceed 1
+/// when beaming is enabled.
+pub fn render_scene_hdr<T: Metric + ?Sized>(
+    metric: &T,
+    camera: impl Camera<T>,
+    objects: Vec<(Emission, Box<dyn RayIntersector<T>>)>,
+    bounds: BoundingBox<T>,
+    settings: RenderSettings<T>,
+) -> Rgb32FImage {
+    let index = index_objects(metric, &objects, bounds);
+    render_indexed(metric, &camera, &objects, &index, bounds, &settings)
+}
+
+/// Render a scene whose objects are already indexed, so that the index can be
+/// reused between images
+pub(crate) fn render_indexed<T: Metric + ?Sized>(
+    metric: &T,
+    camera: &impl Camera<T>,
+    objects: &[(Emission, Box<dyn RayIntersector<T>>)],
+    index: &BroadPhase<T>,
+    bounds: BoundingBox<T>,
+    settings: &RenderSettings<T>,
+) -> Rgb32FImage {
+    let (width, height) = camera.screen_size();
+    let pixels = render_pixels(width, height, settings.threads, |pixel| {
+        trace_pixel(metric, camera, objects, index, bounds, settings, pixel).1
+    });
+    Rgb32FImage::from_fn(width as _, height as _, |x, y| {
+        pixels[y as usize][x as usize]
+    })
+}
+
+/// Convert linear intensities to 8 bit sRGB, clipping intensities above 1
+pub(crate) fn to_ldr(hdr: &Rgb32FImage) -> RgbImage {
+    RgbImage::from_fn(hdr.width(), hdr.height(), |x, y| {
+        to_rgb(hdr.get_pixel(x, y).0.map(|c| c as f64))
+    })
+}
+
+/// Render why the ray through each pixel stopped, colored by
+/// `Termination::color`. Useful for finding rays that run out of steps.
+pub fn render_termination<T: Metric + ?Sized>(
+    metric: &T,
     camera: impl Camera<T>,
-    objects: Vec<(Rgb<u8>, Box<dyn RayIntersector<T>>)>,
+    objects: Vec<(Emission, Box<dyn RayIntersector<T>>)>,
     bounds: BoundingBox<T>,
-    step: f64,
+    settings: RenderSettings<T>,
 ) -> RgbImage {
     let (width, height) = camera.screen_size();
+    let index = index_objects(metric, &objects, bounds);
+    let pixels = render_pixels(width, height, settings.threads, |pixel| {
+        trace_pixel(metric, &camera, &objects, &index, bounds, &settings, pixel).0
+    });
     RgbImage::from_fn(width as _, height as _, |x, y| {
-        let mut lightray = camera.ray((x as _, y as _));
-        while bounds.contains(lightray.root) {
-            lightray = T::step_geodesic(lightray, step);
+        pixels[y as usize][x as usize].color()
+    })
+}
+
+/// Evaluate a function for every pixel, returning rows of results.
+///
+/// Scanlines are handed out to the threads as they become free. Every pixel is
+/// computed independently, so the result does not depend on the number of
+/// threads.
+fn render_pixels<P: Send>(
+    width: usize,
+    height: usize,
+    threads: usize,
+    pixel: impl Fn((usize, usize)) -> P + Sync,
+) -> Vec<Vec<P>> {
+    let next_row = AtomicUsize::new(0);
+    let mut rows = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut rows = vec![];
+                    loop {
+                        let y = next_row.fetch_add(1, Ordering::Relaxed);
+                        if y >= height {
+                            return rows;
+                        }
+                        rows.push((y, (0..width).map(|x| pixel((x, y))).collect::<Vec<_>>()));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    rows.sort_by_key(|(y, _)| *y);
+    rows.into_iter().map(|(_, row)| row).collect()
+}
 
-            for (color, object) in &objects {
-                if object.intersects(lightray, step) {
-                    return *color;
-                }
-            }
+pub(crate) fn index_objects<T: Metric + ?Sized>(
+    metric: &T,
+    objects: &[(Emission, Box<dyn RayIntersector<T>>)],
+    bounds: BoundingBox<T>,
+) -> BroadPhase<T> {
+    let intersectors: Vec<_> = objects.iter().map(|(_, object)| object.as_ref()).collect();
+    BroadPhase::new(metric, bounds, &intersectors)
+}
+
+/// Frequency `k.u` of a camera ray as measured by the observer. Only null rays
+/// have one, rays of instant cameras travel at infinite speed and are shown
+/// without frequency shift and beaming.
+fn observed_frequency<T: Metric + ?Sized>(
+    metric: &T,
+    lightray: ManifoldVector<T>,
+    observer: FourVector,
+) -> Option<f64> {
+    let frequency = metric.inner(lightray.root, observer, lightray.components);
+    let null = metric.norm(lightray).abs() <= 1e-6 * frequency * frequency;
+    (frequency != 0.0 && null).then_some(frequency)
+}
+
+/// Trace the ray through a single pixel back to what emitted it
+fn trace_pixel<T: Metric + ?Sized>(
+    metric: &T,
+    camera: &impl Camera<T>,
+    objects: &[(Emission, Box<dyn RayIntersector<T>>)],
+    index: &BroadPhase<T>,
+    bounds: BoundingBox<T>,
+    settings: &RenderSettings<T>,
+    pixel: (usize, usize),
+) -> (Termination, Rgb<f32>) {
+    let mut lightray = camera.ray(metric, pixel);
+    let observed = observed_frequency(metric, lightray, camera.frame().axis[0]);
+    let mut step = settings.step;
+    // Light emitted by media so far, and the fraction of light from further
+    // along the ray that makes it through them
+    let mut radiance = [0.0; 3];
+    let mut transmittance = 1.0;
+    let mut candidates = vec![];
+    let finish = |termination, behind: [f64; 3], radiance: [f64; 3], transmittance: f64| {
+        let color: [f64; 3] = std::array::from_fn(|i| radiance[i] + transmittance * behind[i]);
+        (termination, Rgb(color.map(|c| c as f32)))
+    };
+    for _ in 0..settings.max_steps {
+        if metric.captured(lightray) {
+            return finish(Termination::Captured, [0.0; 3], radiance, transmittance);
         }
-        Rgb([0, 0, 0])
-    })
+        if !bounds.contains(lightray.root) {
+            let behind = background(metric, lightray, observed, settings);
+            return finish(Termination::Escaped, behind, radiance, transmittance);
+        }
+        if transmittance < 1e-4 {
+            return finish(Termination::Absorbed, [0.0; 3], radiance, transmittance);
+        }
+
+        let next = settings.integrator.step(metric, lightray, step);
+
+        index.candidates(lightray.root, next.ray.root, &mut candidates);
+        let nearest = candidates
+            .iter()
+            .map(|&i| &objects[i])
+            .filter_map(|(emission, object)| {
+                object
+                    .intersects(metric, lightray, next.taken)
+                    .map(|hit| (emission, object, hit))
+            })
+            .min_by(|(_, _, a), (_, _, b)| a.affine.total_cmp(&b.affine));
+
+        // Media up to the hit or the end of the step
+        let length = nearest
+            .as_ref()
+            .map_or(next.taken, |(_, _, hit)| hit.affine);
+        let (emitted, depth) = media_step(metric, lightray, length, observed, settings);
+        let absorbed = (-depth).exp();
+        // Emission and absorption mixed uniformly over the step
+        let weight = if depth > 1e-6 {
+            (1.0 - absorbed) / depth
+        } else {
+            1.0
+        };
+        for i in 0..3 {
+            radiance[i] += transmittance * weight * emitted[i];
+        }
+        transmittance *= absorbed;
+
+        if let Some((emission, object, hit)) = nearest {
+            let local = object.emission(metric, &hit);
+            let emission = local.as_ref().unwrap_or(emission);
+            // Both frequencies are measured on the backwards traced ray,
+            // so k.u is positive for the observer and the emitter alike.
+            let g = observed.map_or(1.0, |k| k / -hit.direction.0[0]);
+            let color = emission.observed(if settings.doppler { g } else { 1.0 });
+            let behind = color.map(|c| c * settings.beaming.factor(g));
+            return finish(Termination::Hit, behind, radiance, transmittance);
+        }
+
+        lightray = next.ray;
+        step = next.next;
+    }
+    finish(
+        Termination::StepBudgetExhausted,
+        [0.0; 3],
+        radiance,
+        transmittance,
+    )
+}
+
+/// Observed emission and optical depth of all media over a step of the given
+/// affine length, sampled at its start
+fn media_step<T: Metric + ?Sized>(
+    metric: &T,
+    lightray: ManifoldVector<T>,
+    length: f64,
+    observed: Option<f64>,
+    settings: &RenderSettings<T>,
+) -> ([f64; 3], f64) {
+    let mut emitted = [0.0; 3];
+    let mut depth = 0.0;
+    for medium in &settings.media {
+        let Some(velocity) = medium.velocity(metric, lightray.root) else {
+            continue;
+        };
+        // k.u is the frequency in the rest frame of the medium, which for a
+        // null ray is also the rate of proper distance per affine parameter.
+        let frequency = metric.inner(lightray.root, velocity, lightray.components);
+        let distance = frequency * length;
+        if distance.is_nan() || distance <= 0.0 {
+            continue;
+        }
+        let g = observed.map_or(1.0, |k| k / frequency);
+        let color = medium.emissivity(metric, lightray.root);
+        let color = if settings.doppler {
+            frequency_shift(color, g)
+        } else {
+            color
+        };
+        for i in 0..3 {
+            emitted[i] += color[i] * settings.beaming.factor(g) * distance;
+        }
+        depth += medium.absorption(metric, lightray.root) * distance;
+    }
+    (emitted, depth)
+}
+
+/// Background seen by a ray that left the scene, with frequency ratio observed
+/// as measured by the camera
+fn background<T: Metric + ?Sized>(
+    metric: &T,
+    lightray: ManifoldVector<T>,
+    observed: Option<f64>,
+    settings: &RenderSettings<T>,
+) -> [f64; 3] {
+    let mut color = settings
+        .background
+        .radiance(metric.escape_direction(lightray));
+    // The sky is emitted by static observers at the point of escape
+    let g = static_velocity(metric, lightray.root)
+        .zip(observed)
+        .map_or(1.0, |(velocity, k)| {
+            k / metric.inner(lightray.root, velocity, lightray.components)
+        });
+    if settings.doppler {
+        color = frequency_shift(color, g);
+    }
+    color.map(|c| c * settings.beaming.factor(g))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        camera::{BasicCamera, InstantCamera, InstantParallelRayCamera, ParallelRayCamera},
+        geometry::ManifoldFrame,
+        metric::{CarthesianMinkowski, testing::coord},
+        objects::SphereCollider,
+    };
+
+    type Objects = Vec<(Emission, Box<dyn RayIntersector<CarthesianMinkowski>>)>;
+
+    fn at(position: [f64; 4]) -> ManifoldFrame<CarthesianMinkowski> {
+        ManifoldFrame::from_four_velocity(
+            &CarthesianMinkowski,
+            coord(position),
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+        )
+    }
+
+    fn white_sphere() -> Objects {
+        vec![(
+            Rgb([255, 255, 255]).into(),
+            Box::new(SphereCollider {
+                center: at([0.0; 4]),
+                radius: 2.0,
+                time_thickness: 1e9,
+            }),
+        )]
+    }
+
+    fn bounds() -> BoundingBox<CarthesianMinkowski> {
+        BoundingBox {
+            bbox: [[-50.0, 50.0], [-20.0, 20.0], [-20.0, 20.0], [-20.0, 20.0]],
+            _metric: std::marker::PhantomData,
+        }
+    }
+
+    fn center_pixel(camera: impl Camera<CarthesianMinkowski>) -> Rgb<u8> {
+        let settings = RenderSettings::new(0.5);
+        assert!(settings.doppler);
+        let image = render_scene(
+            &CarthesianMinkowski,
+            camera,
+            white_sphere(),
+            bounds(),
+            settings,
+        );
+        *image.get_pixel(2, 2)
+    }
+
+    #[test]
+    fn every_camera_kind_shows_a_static_white_sphere() {
+        let frame = at([0.0, 0.0, 0.0, -10.0]);
+        let white = Rgb([255, 255, 255]);
+        assert_eq!(center_pixel(BasicCamera::new(frame, 5, 5, 1.0)), white);
+        assert_eq!(center_pixel(InstantCamera::new(frame, 5, 5, 1.0)), white);
+        assert_eq!(
+            center_pixel(ParallelRayCamera::new(frame, 5, 5, 1.0)),
+            white
+        );
+        assert_eq!(
+            center_pixel(InstantParallelRayCamera::new(frame, 5, 5, 1.0)),
+            white
+        );
+    }
+
+    #[test]
+    fn thread_count_does_not_change_the_image() {
+        let render = |threads| {
+            let objects: Objects = (0..6)
+                .map(|i| {
+                    let x = i as f64 * 1.5 - 3.75;
+                    let object: Box<dyn RayIntersector<CarthesianMinkowski>> =
+                        Box::new(SphereCollider {
+                            center: at([0.0, x, 0.3 * x, 2.0 * (i % 2) as f64]),
+                            radius: 0.7,
+                            time_thickness: 1e9,
+                        });
+                    (Rgb([40 * i as u8, 255 - 40 * i as u8, 128]).into(), object)
+                })
+                .collect();
+            render_scene(
+                &CarthesianMinkowski,
+                BasicCamera::new(at([0.0, 0.0, 0.0, -10.0]), 24, 16, 1.0),
+                objects,
+                bounds(),
+                RenderSettings::new(0.5).with_threads(threads),
+            )
+        };
+        let single = render(1);
+        assert!(single.pixels().any(|p| *p != single[(0, 0)]));
+        assert_eq!(single, render(4));
+    }
 }
@@ -0,0 +1,499 @@
+//! Declarative scene descriptions in TOML, rendered by the `grrender` binary.
+//!
+//! ```toml
+//! output = "lensing.png"
+//! bounds = [[-200.0, 1.0], [-40.0, 40.0], [-40.0, 40.0], [-40.0, 40.0]]
+//!
+//! [metric]
+//! type = "schwarzschild"
+//! mass = 1.0
+//!
+//! [camera]
+//! type = "basic"              # basic, instant, parallel or instant_parallel
+//! position = [0.0, 0.0, 0.0, -30.0]
+//! width = 300
+//! height = 200
+//! scale = 1.2
+//!
+//! [settings]
+//! step = 0.2
+//! integrator = { dormand_prince = 1e-6 }
+//! background = [10, 10, 30]   # or the path of a sky map
+//!
+//! [[objects]]
+//! type = "sphere"
+//! position = [0.0, 0.0, 0.0, 15.0]
+//! radius = 3.0
+//! emission = [255, 200, 80]   # or { temperature = 5800.0 }
+//! ```
+//!
+//! Frames, of the camera and of objects, are given by the `position` of their
+//! origin and optionally their coordinate `velocity` or all four `axes`,
+//! which default to the coordinate axes. They are orthonormalized with
+//! `ManifoldFrame::normalize`. Relative paths are relative to the scene file.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use image::{ImageResult, RgbImage};
+use serde::Deserialize;
+
+use crate::{
+    background::{Background, SkyMap, Uniform},
+    camera::{BasicCamera, InstantCamera, InstantParallelRayCamera, ParallelRayCamera},
+    color::from_rgb,
+    geometry::{BoundingBox, Coord, FourVector, ManifoldFrame, SpatialVec},
+    integrator::{DormandPrince, Euler, Integrator, Native, RungeKutta4},
+    metric::{CarthesianMinkowski, Kerr, KerrSchild, Metric, Schwarzschild},
+    objects::{
+        BoxCollider, CylinderCollider, DiscCollider, Mesh, PlaneCollider, RayIntersector,
+        SphereCollider, TorusCollider,
+    },
+    render::{Beaming, RenderSettings, render_scene},
+    spectrum::Emission,
+};
+
+/// Everything needed to render an image
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scene {
+    /// Where the rendered image is saved
+    pub output: PathBuf,
+    pub metric: MetricSpec,
+    pub camera: CameraSpec,
+    /// Coordinate box outside of which rays escape, see `BoundingBox`
+    pub bounds: [[f64; 2]; 4],
+    #[serde(default)]
+    pub settings: SettingsSpec,
+    #[serde(default)]
+    pub objects: Vec<ObjectSpec>,
+    /// Directory relative paths are resolved against
+    #[serde(skip)]
+    pub base: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MetricSpec {
+    Minkowski,
+    Schwarzschild { mass: f64 },
+    Kerr { mass: f64, spin: f64 },
+    KerrSchild { mass: f64, spin: f64 },
+}
+
+/// Rest frame given by its origin, and either its coordinate velocity or its
+/// axes
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct FrameSpec {
+    pub position: [f64; 4],
+    pub velocity: Option<[f64; 3]>,
+    pub axes: Option<[[f64; 4]; 4]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CameraKind {
+    #[default]
+    Basic,
+    Instant,
+    Parallel,
+    InstantParallel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct CameraSpec {
+    #[serde(default, rename = "type")]
+    pub kind: CameraKind,
+    #[serde(flatten)]
+    pub frame: FrameSpec,
+    pub width: usize,
+    pub height: usize,
+    pub scale: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegratorSpec {
+    #[default]
+    Native,
+    Euler,
+    Rk4,
+    /// Adaptive, with the given tolerance
+    DormandPrince(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BeamingSpec {
+    #[default]
+    Off,
+    Specific,
+    Bolometric,
+}
+
+/// Uniform 8 bit sRGB color, or the path of an equirectangular sky map
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum BackgroundSpec {
+    Color([u8; 3]),
+    SkyMap(PathBuf),
+}
+
+impl Default for BackgroundSpec {
+    fn default() -> Self {
+        BackgroundSpec::Color([0, 0, 0])
+    }
+}
+
+/// See `RenderSettings`, which provides the defaults
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SettingsSpec {
+    pub step: f64,
+    pub integrator: IntegratorSpec,
+    pub doppler: bool,
+    pub beaming: BeamingSpec,
+    pub max_steps: Option<usize>,
+    pub background: BackgroundSpec,
+    pub threads: Option<usize>,
+}
+
+impl Default for SettingsSpec {
+    fn default() -> Self {
+        SettingsSpec {
+            step: 0.1,
+            integrator: IntegratorSpec::default(),
+            doppler: true,
+            beaming: BeamingSpec::default(),
+            max_steps: None,
+            background: BackgroundSpec::default(),
+            threads: None,
+        }
+    }
+}
+
+/// 8 bit sRGB color, or a blackbody
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum EmissionSpec {
+    Color([u8; 3]),
+    Blackbody {
+        temperature: f64,
+        #[serde(default = "one")]
+        luminance: f64,
+    },
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+fn forever() -> f64 {
+    1e9
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ObjectSpec {
+    pub emission: EmissionSpec,
+    #[serde(flatten)]
+    pub frame: FrameSpec,
+    /// Objects exist for local times within plus or minus this, forever by
+    /// default
+    #[serde(default = "forever")]
+    pub time_thickness: f64,
+    #[serde(flatten)]
+    pub shape: ShapeSpec,
+}
+
+/// Shapes in the local rest frame, see the colliders of the same name
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShapeSpec {
+    Sphere {
+        radius: f64,
+    },
+    Box {
+        half_size: [f64; 3],
+    },
+    Cylinder {
+        radius: f64,
+        half_height: f64,
+    },
+    Torus {
+        major_radius: f64,
+        minor_radius: f64,
+    },
+    Disc {
+        #[serde(default)]
+        inner_radius: f64,
+        outer_radius: f64,
+    },
+    Plane {
+        normal: [f64; 3],
+    },
+    /// OBJ or PLY file
+    Mesh {
+        path: PathBuf,
+    },
+}
+
+impl Scene {
+    pub fn parse(source: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(source)
+    }
+
+    /// Load a scene file, resolving relative paths in it against its
+    /// directory
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut scene = Self::parse(&std::fs::read_to_string(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        scene.base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(scene)
+    }
+
+    /// Output path, resolved against the scene directory
+    pub fn output_path(&self) -> PathBuf {
+        self.base.join(&self.output)
+    }
+
+    /// Render the scene, failing with `InvalidData` for an empty image
+    pub fn render(&self) -> ImageResult<RgbImage> {
+        if self.camera.width == 0 || self.camera.height == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "camera width and height must be positive",
+            )
+            .into());
+        }
+        match self.metric {
+            MetricSpec::Minkowski => self.render_in(&CarthesianMinkowski),
+            MetricSpec::Schwarzschild { mass } => self.render_in(&Schwarzschild::new(mass)),
+            MetricSpec::Kerr { mass, spin } => self.render_in(&Kerr::new(mass, spin)),
+            MetricSpec::KerrSchild { mass, spin } => self.render_in(&KerrSchild::new(mass, spin)),
+        }
+    }
+
+    fn render_in<T: Metric + 'static>(&self, metric: &T) -> ImageResult<RgbImage> {
+        let objects = self
+            .objects
+            .iter()
+            .map(|object| object.build(metric, &self.base))
+            .collect::<io::Result<Vec<_>>>()?;
+        let settings = self.settings.build(&self.base)?;
+        let bounds = BoundingBox {
+            bbox: self.bounds,
+            _metric: std::marker::PhantomData,
+        };
+        let CameraSpec {
+            kind,
+            width,
+            height,
+            scale,
+            ..
+        } = self.camera;
+        let frame = self.camera.frame.build(metric);
+        Ok(match kind {
+            CameraKind::Basic => render_scene(
+                metric,
+                BasicCamera::new(frame, width, height, scale),
+                objects,
+                bounds,
+                settings,
+            ),
+            CameraKind::Instant => render_scene(
+                metric,
+                InstantCamera::new(frame, width, height, scale),
+                objects,
+                bounds,
+                settings,
+            ),
+            CameraKind::Parallel => render_scene(
+                metric,
+                ParallelRayCamera::new(frame, width, height, scale),
+                objects,
+                bounds,
+                settings,
+            ),
+            CameraKind::InstantParallel => render_scene(
+                metric,
+                InstantParallelRayCamera::new(frame, width, height, scale),
+                objects,
+                bounds,
+                settings,
+            ),
+        })
+    }
+}
+
+impl FrameSpec {
+    pub fn build<T: Metric + ?Sized>(&self, metric: &T) -> ManifoldFrame<T> {
+        let mut axis = self
+            .axes
+            .unwrap_or([
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ])
+            .map(FourVector);
+        if let Some([vx, vy, vz]) = self.velocity {
+            axis[0] = FourVector([1.0, vx, vy, vz]);
+        }
+        ManifoldFrame {
+            root: Coord {
+                components: FourVector(self.position),
+                _metric: std::marker::PhantomData,
+            },
+            axis,
+        }
+        .normalize(metric)
+    }
+}
+
+impl SettingsSpec {
+    pub fn build<T: Metric + ?Sized>(&self, base: &Path) -> ImageResult<RenderSettings<T>> {
+        let integrator: Box<dyn Integrator<T>> = match self.integrator {
+            IntegratorSpec::Native => Box::new(Native),
+            IntegratorSpec::Euler => Box::new(Euler),
+            IntegratorSpec::Rk4 => Box::new(RungeKutta4),
+            IntegratorSpec::DormandPrince(tolerance) => Box::new(DormandPrince::new(tolerance)),
+        };
+        let background: Box<dyn Background> = match &self.background {
+            BackgroundSpec::Color(color) => Box::new(Uniform(from_rgb(image::Rgb(*color)))),
+            BackgroundSpec::SkyMap(path) => Box::new(SkyMap::open(base.join(path))?),
+        };
+        let defaults = RenderSettings::new(self.step);
+        Ok(RenderSettings {
+            integrator,
+            doppler: self.doppler,
+            beaming: match self.beaming {
+                BeamingSpec::Off => Beaming::Off,
+                BeamingSpec::Specific => Beaming::Specific,
+                BeamingSpec::Bolometric => Beaming::Bolometric,
+            },
+            max_steps: self.max_steps.unwrap_or(defaults.max_steps),
+            background,
+            threads: self.threads.unwrap_or(defaults.threads),
+            ..defaults
+        })
+    }
+}
+
+impl From<EmissionSpec> for Emission {
+    fn from(spec: EmissionSpec) -> Self {
+        match spec {
+            EmissionSpec::Color(color) => image::Rgb(color).into(),
+            EmissionSpec::Blackbody {
+                temperature,
+                luminance,
+            } => Emission::Blackbody {
+                temperature,
+                luminance,
+            },
+        }
+    }
+}
+
+impl ObjectSpec {
+    pub fn build<T: Metric + 'static>(
+        &self,
+        metric: &T,
+        base: &Path,
+    ) -> io::Result<(Emission, Box<dyn RayIntersector<T>>)> {
+        let frame = self.frame.build(metric);
+        let time_thickness = self.time_thickness;
+        let object: Box<dyn RayIntersector<T>> = match &self.shape {
+            &ShapeSpec::Sphere { radius } => Box::new(SphereCollider {
+                center: frame,
+                radius,
+                time_thickness,
+            }),
+            &ShapeSpec::Box { half_size } => Box::new(BoxCollider {
+                frame,
+                half_size: SpatialVec(half_size),
+                time_thickness,
+            }),
+            &ShapeSpec::Cylinder {
+                radius,
+                half_height,
+            } => Box::new(CylinderCollider {
+                frame,
+                radius,
+                half_height,
+                time_thickness,
+            }),
+            &ShapeSpec::Torus {
+                major_radius,
+                minor_radius,
+            } => Box::new(TorusCollider {
+                frame,
+                major_radius,
+                minor_radius,
+                time_thickness,
+            }),
+            &ShapeSpec::Disc {
+                inner_radius,
+                outer_radius,
+            } => Box::new(DiscCollider {
+                frame,
+                inner_radius,
+                outer_radius,
+                time_thickness,
+            }),
+            &ShapeSpec::Plane { normal } => Box::new(PlaneCollider {
+                frame,
+                normal: SpatialVec(normal),
+                time_thickness,
+            }),
+            ShapeSpec::Mesh { path } => {
+                Box::new(Mesh::open(base.join(path), frame, time_thickness)?)
+            }
+        };
+        Ok((self.emission.into(), object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENE: &str = r#"
+        output = "test.png"
+        bounds = [[-50.0, 1.0], [-20.0, 20.0], [-20.0, 20.0], [-20.0, 20.0]]
+
+        [metric]
+        type = "minkowski"
+
+        [camera]
+        position = [0.0, 0.0, 0.0, -10.0]
+        width = 4
+        height = 3
+        scale = 1.0
+
+        [settings]
+        step = 0.5
+
+        [[objects]]
+        type = "sphere"
+        position = [0.0, 0.0, 0.0, 5.0]
+        radius = 2.0
+        emission = [255, 200, 80]
+    "#;
+
+    #[test]
+    fn renders_camera_size() {
+        let image = Scene::parse(SCENE).unwrap().render().unwrap();
+        assert_eq!(image.dimensions(), (4, 3));
+    }
+
+    #[test]
+    fn rejects_empty_camera() {
+        for (from, to) in [("width = 4", "width = 0"), ("height = 3", "height = 0")] {
+            let scene = Scene::parse(&SCENE.replace(from, to)).unwrap();
+            assert!(scene.render().is_err());
+        }
+    }
+}
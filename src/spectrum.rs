@@ -0,0 +1,183 @@
+use image::Rgb;
+
+use crate::color::{frequency_shift, from_rgb};
+
+/// Visible range (nm) and step used when integrating spectra
+const WAVELENGTH_RANGE: (f64, f64) = (380.0, 780.0);
+const WAVELENGTH_STEP: f64 = 5.0;
+
+/// Second radiation constant hc/k, in nm K
+const PLANCK_C2: f64 = 1.438777e7;
+
+/// Conversion from CIE XYZ to linear sRGB primaries
+const XYZ_TO_SRGB: [[f64; 3]; 3] = [
+    [3.2406, -1.5372, -0.4986],
+    [-0.9689, 1.8758, 0.0415],
+    [0.0557, -0.2040, 1.0570],
+];
+
+fn piecewise_gaussian(wavelength: f64, mean: f64, lower: f64, upper: f64) -> f64 {
+    let width = if wavelength < mean { lower } else { upper };
+    (-0.5 * ((wavelength - mean) / width).powi(2)).exp()
+}
+
+/// CIE 1931 2 degree color matching functions, using the multi-lobe gaussian
+/// fit of Wyman, Sloan and Shirley (2013).
+pub fn color_matching(wavelength: f64) -> [f64; 3] {
+    let l = wavelength;
+    [
+        1.056 * piecewise_gaussian(l, 599.8, 37.9, 31.0)
+            + 0.362 * piecewise_gaussian(l, 442.0, 16.0, 26.7)
+            - 0.065 * piecewise_gaussian(l, 501.1, 20.4, 26.2),
+        0.821 * piecewise_gaussian(l, 568.8, 46.9, 40.5)
+            + 0.286 * piecewise_gaussian(l, 530.9, 16.3, 31.1),
+        1.217 * piecewise_gaussian(l, 437.0, 11.8, 36.0)
+            + 0.681 * piecewise_gaussian(l, 459.0, 26.0, 13.8),
+    ]
+}
+
+/// Integrate a spectral radiance per unit frequency over the visible range into
+/// CIE XYZ. The matching functions are per unit wavelength, so the radiance is
+/// converted with `dnu/dlambda ~ 1/lambda^2` first.
+pub fn spectrum_to_xyz(radiance: impl Fn(f64) -> f64) -> [f64; 3] {
+    let (start, end) = WAVELENGTH_RANGE;
+    let steps = ((end - start) / WAVELENGTH_STEP) as usize;
+    let mut xyz = [0.0; 3];
+    for i in 0..=steps {
+        let wavelength = start + i as f64 * WAVELENGTH_STEP;
+        let value = radiance(wavelength) / (wavelength * wavelength);
+        let cmf = color_matching(wavelength);
+        for c in 0..3 {
+            xyz[c] += value * cmf[c] * WAVELENGTH_STEP;
+        }
+    }
+    xyz
+}
+
+/// Convert CIE XYZ to linear sRGB, dropping out of gamut negative components
+pub fn xyz_to_linear_srgb(xyz: [f64; 3]) -> [f64; 3] {
+    XYZ_TO_SRGB.map(|row| (0..3).map(|i| row[i] * xyz[i]).sum::<f64>().max(0.0))
+}
+
+/// Relative spectral radiance per unit frequency of a blackbody
+pub fn planck(wavelength: f64, temperature: f64) -> f64 {
+    1.0 / (wavelength.powi(3) * ((PLANCK_C2 / (wavelength * temperature)).exp_m1()))
+}
+
+/// How an object emits light
+#[derive(Debug, Clone, PartialEq)]
+pub enum Emission {
+    /// Fixed color. Frequency shifts only move its hue, see
+    /// `color::frequency_shift`.
+    Color(Rgb<u8>),
+    /// Blackbody of the given temperature in kelvin. Luminance is that of the
+    /// unshifted emission, in units where 1 is white.
+    Blackbody { temperature: f64, luminance: f64 },
+    /// Spectrum sampled as (wavelength in nm, relative radiance per unit
+    /// frequency) pairs in increasing wavelength order, linearly interpolated
+    /// and zero outside the samples. Luminance is that of the unshifted
+    /// emission, in units where 1 is white.
+    Spectrum {
+        samples: Vec<(f64, f64)>,
+        luminance: f64,
+    },
+}
+
+impl From<Rgb<u8>> for Emission {
+    fn from(color: Rgb<u8>) -> Self {
+        Emission::Color(color)
+    }
+}
+
+impl Emission {
+    /// Emitted radiance at a wavelength, in arbitrary units
+    fn radiance(&self, wavelength: f64) -> f64 {
+        match self {
+            Emission::Color(_) => 0.0,
+            Emission::Blackbody { temperature, .. } => planck(wavelength, *temperature),
+            Emission::Spectrum { samples, .. } => {
+                let i = samples.partition_point(|(l, _)| *l < wavelength);
+                if i == 0 || i == samples.len() {
+                    return 0.0;
+                }
+                let (l0, v0) = samples[i - 1];
+                let (l1, v1) = samples[i];
+                v0 + (v1 - v0) * (wavelength - l0) / (l1 - l0)
+            }
+        }
+    }
+
+    /// Color of the emission as seen by an observer receiving it with
+    /// frequency ratio g = nu_obs / nu_emit, in linear sRGB. This only shifts
+    /// the spectrum, scaling of the intensity is handled by beaming.
+    pub fn observed(&self, g: f64) -> [f64; 3] {
+        match self {
+            Emission::Color(color) => frequency_shift(from_rgb(*color), g),
+            Emission::Blackbody { luminance, .. } | Emission::Spectrum { luminance, .. } => {
+                let reference = spectrum_to_xyz(|l| self.radiance(l))[1];
+                if *luminance == 0.0 || reference <= 0.0 {
+                    return [0.0; 3];
+                }
+                // Light observed at wavelength l was emitted at g * l
+                let xyz = spectrum_to_xyz(|l| self.radiance(g * l));
+                xyz_to_linear_srgb(xyz.map(|c| c * luminance / reference))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chromaticity(xyz: [f64; 3]) -> [f64; 2] {
+        let sum = xyz[0] + xyz[1] + xyz[2];
+        [xyz[0] / sum, xyz[1] / sum]
+    }
+
+    #[test]
+    fn blackbody_6500k_is_near_d65() {
+        let [x, y] = chromaticity(spectrum_to_xyz(|l| planck(l, 6500.0)));
+        assert!((x - 0.3127).abs() < 0.005, "x = {x}");
+        assert!((y - 0.3290).abs() < 0.01, "y = {y}");
+    }
+
+    #[test]
+    fn blackbody_5800k_chromaticity() {
+        let [x, y] = chromaticity(spectrum_to_xyz(|l| planck(l, 5800.0)));
+        assert!((x - 0.326).abs() < 0.005, "x = {x}");
+        assert!((y - 0.335).abs() < 0.005, "y = {y}");
+    }
+
+    #[test]
+    fn unshifted_blackbody_has_its_luminance() {
+        let emission = Emission::Blackbody {
+            temperature: 6500.0,
+            luminance: 1.0,
+        };
+        let rgb = emission.observed(1.0);
+        // Linear sRGB luminance of the D65 white point is one
+        let luminance = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+        assert!((luminance - 1.0).abs() < 0.02, "luminance = {luminance}");
+        assert!(rgb.iter().all(|c| (c - 1.0).abs() < 0.1), "rgb = {rgb:?}");
+    }
+
+    #[test]
+    fn shift_by_g_matches_blackbody_of_scaled_temperature() {
+        // A blackbody seen with frequency ratio g has the spectral shape of a
+        // blackbody of temperature g T
+        let emission = Emission::Blackbody {
+            temperature: 4000.0,
+            luminance: 1.0,
+        };
+        let shifted = chromaticity(spectrum_to_xyz(|l| planck(1.5 * l, 4000.0)));
+        let hotter = chromaticity(spectrum_to_xyz(|l| planck(l, 6000.0)));
+        assert!((shifted[0] - hotter[0]).abs() < 1e-9);
+        assert!((shifted[1] - hotter[1]).abs() < 1e-9);
+        // Only the spectrum shifts, a blueshift brightens without beaming
+        let rgb = emission.observed(1.5);
+        assert!(rgb.iter().all(|c| c.is_finite() && *c > 0.0));
+        let luminance = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+        assert!(luminance > 1.0);
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    f64::consts::{PI, TAU},
+    path::Path,
+};
+
+use image::{ImageResult, RgbImage};
+
+use crate::{
+    geometry::{BoundingBox, ManifoldVector, SpatialVec},
+    metric::Metric,
+    objects::{Hit, Interval, RayIntersector},
+    spectrum::Emission,
+    util::non_empty,
+};
+
+/// Emission over the surface of an object, as a function of the hit in the
+/// local rest frame of the object.
+pub trait Texture: Sync {
+    /// Emission at a local position with outward normal, at the given proper
+    /// time of the object
+    fn emission(&self, position: SpatialVec, normal: SpatialVec, proper_time: f64) -> Emission;
+}
+
+/// How local positions map to texture coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mapping {
+    /// Projection along the local axis closest to the normal onto the other
+    /// two, in units of size. Suits planes and the faces of boxes.
+    Planar { size: f64 },
+    /// Longitude and latitude around the origin of the frame, both scaled to
+    /// `[0, 1]`. The pole at v = 0 is the -y direction and u = 0.5 is +z, like
+    /// `SkyMap`.
+    Spherical,
+}
+
+impl Mapping {
+    pub fn uv(&self, position: SpatialVec, normal: SpatialVec) -> [f64; 2] {
+        let [x, y, z] = position.0;
+        match *self {
+            Mapping::Planar { size } => {
+                let [nx, ny, nz] = normal.0.map(f64::abs);
+                let (u, v) = if nz >= nx && nz >= ny {
+                    (x, y)
+                } else if ny >= nx {
+                    (x, z)
+                } else {
+                    (z, y)
+                };
+                [u / size, v / size]
+            }
+            Mapping::Spherical => {
+                let r = (x * x + y * y + z * z).sqrt();
+                let longitude = x.atan2(z);
+                let colatitude = (-y / r).clamp(-1.0, 1.0).acos();
+                [0.5 + longitude / TAU, colatitude / PI]
+            }
+        }
+    }
+}
+
+/// Alternating squares of two emissions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkerboard {
+    pub mapping: Mapping,
+    pub colors: [Emission; 2],
+}
+
+impl Texture for Checkerboard {
+    fn emission(&self, position: SpatialVec, normal: SpatialVec, _proper_time: f64) -> Emission {
+        let [u, v] = self.mapping.uv(position, normal);
+        let parity = (u.floor() + v.floor()).rem_euclid(2.0) as usize;
+        self.colors[parity].clone()
+    }
+}
+
+/// Lines of constant longitude and latitude, as on a globe
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatLongGrid {
+    /// Angle between neighbouring lines, in radians
+    pub spacing: f64,
+    /// Width of the lines, in radians
+    pub width: f64,
+    pub line: Emission,
+    pub fill: Emission,
+}
+
+impl Texture for LatLongGrid {
+    fn emission(&self, position: SpatialVec, normal: SpatialVec, _proper_time: f64) -> Emission {
+        let [u, v] = Mapping::Spherical.uv(position, normal);
+        // Distance to the nearest line, in radians
+        let distance = |angle: f64| {
+            let offset = angle.rem_euclid(self.spacing);
+            offset.min(self.spacing - offset)
+        };
+        let latitude = distance(v * PI);
+        // Meridians converge at the poles, keep their width constant
+        let longitude = distance(u * TAU) * (v * PI).sin();
+        if latitude.min(longitude) < 0.5 * self.width {
+            self.line.clone()
+        } else {
+            self.fill.clone()
+        }
+    }
+}
+
+/// Image stretched over the texture coordinates, repeating outside `[0, 1]`.
+/// Sampled at the nearest pixel, so the image must not be empty.
+#[derive(Debug, Clone)]
+pub struct ImageTexture {
+    pub mapping: Mapping,
+    image: RgbImage,
+}
+
+impl ImageTexture {
+    /// Texture showing an 8 bit sRGB image, which must not be empty
+    pub fn new(image: RgbImage, mapping: Mapping) -> ImageResult<Self> {
+        non_empty(image.width(), image.height())?;
+        Ok(ImageTexture { mapping, image })
+    }
+
+    /// Load an 8 bit sRGB image from disk
+    pub fn open(path: impl AsRef<Path>, mapping: Mapping) -> ImageResult<Self> {
+        Self::new(image::open(path)?.into_rgb8(), mapping)
+    }
+
+    /// The image shown by the texture
+    pub fn image(&self) -> &RgbImage {
+        &self.image
+    }
+}
+
+impl Texture for ImageTexture {
+    fn emission(&self, position: SpatialVec, normal: SpatialVec, _proper_time: f64) -> Emission {
+        let [u, v] = self.mapping.uv(position, normal);
+        let (width, height) = (self.image.width(), self.image.height());
+        let x = (u.rem_euclid(1.0) * width as f64) as u32;
+        let y = (v.rem_euclid(1.0) * height as f64) as u32;
+        (*self.image.get_pixel(x.min(width - 1), y.min(height - 1))).into()
+    }
+}
+
+/// Analog clock showing the proper time of the object, centered on the
+/// origin of its frame and drawn in the plane of a planar mapping. The hand
+/// turns once per period, starting from -v at zero proper time, with twelve
+/// marks around the rim. Comparing clocks shows time dilation and the
+/// relativity of simultaneity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockFace {
+    pub radius: f64,
+    /// Proper time for one turn of the hand
+    pub period: f64,
+    /// Color of the dial, and of everything outside it
+    pub face: Emission,
+    /// Color of the hand, the marks and the rim
+    pub marks: Emission,
+}
+
+impl Texture for ClockFace {
+    fn emission(&self, position: SpatialVec, normal: SpatialVec, proper_time: f64) -> Emission {
+        let [u, v] = Mapping::Planar { size: self.radius }.uv(position, normal);
+        let r = (u * u + v * v).sqrt();
+        // Clockwise from -v, which is up on screen for the usual camera
+        let angle = u.atan2(-v);
+        let width = 0.04;
+
+        let rim = (r - 0.95).abs() < width;
+        let step = TAU / 12.0;
+        let offset = angle.rem_euclid(step);
+        let mark = r > 0.75 && r < 0.95 && offset.min(step - offset) * r < width;
+        let hand_angle = TAU * (proper_time / self.period).rem_euclid(1.0);
+        let along = u * hand_angle.sin() - v * hand_angle.cos();
+        let across = u * hand_angle.cos() + v * hand_angle.sin();
+        let hand = along > -0.1 && along < 0.8 && across.abs() < width;
+
+        if rim || mark || hand {
+            self.marks.clone()
+        } else {
+            self.face.clone()
+        }
+    }
+}
+
+/// Object whose emission comes from a texture, in place of the emission it
+/// was added to the scene with
+pub struct Textured<T: Metric + ?Sized> {
+    pub object: Box<dyn RayIntersector<T>>,
+    pub texture: Box<dyn Texture>,
+}
+
+impl<T: Metric + ?Sized> Textured<T> {
+    pub fn new(object: impl RayIntersector<T> + 'static, texture: impl Texture + 'static) -> Self {
+        Textured {
+            object: Box::new(object),
+            texture: Box::new(texture),
+        }
+    }
+}
+
+impl<T: Metric + ?Sized> RayIntersector<T> for Textured<T> {
+    fn intersects(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Option<Hit<T>> {
+        self.object.intersects(metric, ray, stepsize)
+    }
+
+    fn intervals(&self, metric: &T, ray: ManifoldVector<T>, stepsize: f64) -> Vec<Interval<T>> {
+        self.object.intervals(metric, ray, stepsize)
+    }
+
+    fn emission(&self, _metric: &T, hit: &Hit<T>) -> Option<Emission> {
+        Some(
+            self.texture
+                .emission(hit.position, hit.normal, hit.proper_time),
+        )
+    }
+
+    fn in_bounding_box(&self, metric: &T, bbox: BoundingBox<T>) -> bool {
+        self.object.in_bounding_box(metric, bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_texture_rejects_empty_image() {
+        assert!(ImageTexture::new(RgbImage::new(0, 0), Mapping::Spherical).is_err());
+        assert!(ImageTexture::new(RgbImage::new(3, 0), Mapping::Spherical).is_err());
+    }
+
+    #[test]
+    fn image_texture_repeats() {
+        let image = RgbImage::from_fn(2, 1, |x, _| image::Rgb([x as u8 * 255, 0, 0]));
+        let texture = ImageTexture::new(image, Mapping::Planar { size: 1.0 }).unwrap();
+        let normal = SpatialVec([0.0, 0.0, 1.0]);
+        let at = |x: f64| texture.emission(SpatialVec([x, 0.25, 0.0]), normal, 0.0);
+        assert_eq!(at(0.25), at(1.25));
+        assert_eq!(at(0.75), at(-0.25));
+        assert_ne!(at(0.25), at(0.75));
+    }
+}
@@ -0,0 +1,252 @@
+use std::array;
+
+use crate::{
+    geometry::{Coord, FourVector, ManifoldFrame, ManifoldVector, SpatialVec},
+    metric::{Christoffel, Metric},
+};
+
+/// Change of a vector b transported along a, `-Gamma^mu_ab a^a b^b`
+fn connection(gamma: &Christoffel, a: FourVector, b: FourVector) -> FourVector {
+    FourVector(array::from_fn(|mu| {
+        -(0..4)
+            .flat_map(|i| (0..4).map(move |j| (i, j)))
+            .map(|(i, j)| gamma[mu][i][j] * a.0[i] * b.0[j])
+            .sum::<f64>()
+    }))
+}
+
+/// Position and frame axes, as integrated
+#[derive(Clone, Copy)]
+struct State([FourVector; 5]);
+
+impl State {
+    fn of<T: Metric + ?Sized>(frame: ManifoldFrame<T>) -> Self {
+        let [t, x, y, z] = frame.axis;
+        State([frame.root.components, t, x, y, z])
+    }
+
+    fn frame<T: Metric + ?Sized>(&self) -> ManifoldFrame<T> {
+        ManifoldFrame {
+            root: Coord {
+                components: self.0[0],
+                _metric: std::marker::PhantomData,
+            },
+            axis: [self.0[1], self.0[2], self.0[3], self.0[4]],
+        }
+    }
+
+    fn add(&self, scale: f64, derivative: &State) -> State {
+        State(array::from_fn(|i| self.0[i] + scale * derivative.0[i]))
+    }
+
+    /// Classic fourth order Runge-Kutta step, with the derivative as a
+    /// function of the parameter offset within the step and the state
+    fn rk4(&self, step: f64, derivative: impl Fn(f64, &State) -> State) -> State {
+        let k1 = derivative(0.0, self);
+        let k2 = derivative(0.5 * step, &self.add(0.5 * step, &k1));
+        let k3 = derivative(0.5 * step, &self.add(0.5 * step, &k2));
+        let k4 = derivative(step, &self.add(step, &k3));
+        State(array::from_fn(|i| {
+            self.0[i] + step / 6.0 * (k1.0[i] + 2.0 * k2.0[i] + 2.0 * k3.0[i] + k4.0[i])
+        }))
+    }
+}
+
+/// Parallel transport the axes of a frame along a curve, from parameter start
+/// to end in the given number of fourth order Runge-Kutta steps. The curve
+/// gives its position and tangent at each parameter, and the frame is moved to
+/// its end.
+///
+/// Parallel transport keeps inner products, so orthonormal frames stay
+/// orthonormal up to integration error. Along a curve that is not a geodesic
+/// the time axis of the frame generally no longer follows the curve, use
+/// `fermi_walker` for the rest frames of accelerated observers.
+pub fn parallel_transport<T: Metric + ?Sized>(
+    metric: &T,
+    frame: ManifoldFrame<T>,
+    curve: impl Fn(f64) -> ManifoldVector<T>,
+    start: f64,
+    end: f64,
+    steps: usize,
+) -> ManifoldFrame<T> {
+    let step = (end - start) / steps.max(1) as f64;
+    let mut state = State::of(frame);
+    state.0[0] = curve(start).root.components;
+    for n in 0..steps.max(1) {
+        let from = start + n as f64 * step;
+        state = state.rk4(step, |offset, state| {
+            let tangent = curve(from + offset);
+            let gamma = metric.christoffel(tangent.root);
+            State([
+                tangent.components,
+                connection(&gamma, tangent.components, state.0[1]),
+                connection(&gamma, tangent.components, state.0[2]),
+                connection(&gamma, tangent.components, state.0[3]),
+                connection(&gamma, tangent.components, state.0[4]),
+            ])
+        });
+        state.0[0] = curve(from + step).root.components;
+    }
+    state.frame()
+}
+
+/// Move an observer along its worldline by the given proper time, Fermi-Walker
+/// transporting its rest frame, with fourth order Runge-Kutta steps of at most
+/// `step`. The time axis of the frame is the four velocity, and the proper
+/// acceleration is given along the spatial axes as a function of proper time,
+/// counted from start. Without acceleration the observer follows a geodesic
+/// and this is parallel transport.
+///
+/// Fermi-Walker transported axes do not rotate as seen by the observer, they
+/// follow the gyroscopes it carries along. For the spatial axes e and four
+/// velocity u, `De/dtau = (e.a) u - (e.u) a`, where `e.u` vanishes and `e.a`
+/// is the acceleration along e. The frame is orthonormalized after every
+/// step against accumulating errors.
+///
+/// Panics unless `step` is positive and finite, and `duration` finite.
+pub fn fermi_walker<T: Metric + ?Sized>(
+    metric: &T,
+    frame: ManifoldFrame<T>,
+    acceleration: impl Fn(f64) -> SpatialVec,
+    start: f64,
+    duration: f64,
+    step: f64,
+) -> ManifoldFrame<T> {
+    assert!(
+        step > 0.0 && step.is_finite(),
+        "transport step must be positive and finite, not {step}"
+    );
+    assert!(
+        duration.is_finite(),
+        "transport duration must be finite, not {duration}"
+    );
+    let steps = (duration.abs() / step).ceil().max(1.0) as usize;
+    let dtau = duration / steps as f64;
+    let mut frame = frame;
+    for n in 0..steps {
+        let from = start + n as f64 * dtau;
+        let state = State::of(frame).rk4(dtau, |offset, state| {
+            let a = acceleration(from + offset).0;
+            let frame = state.frame::<T>();
+            let gamma = metric.christoffel(frame.root);
+            let u = frame.axis[0];
+            let along: FourVector = (0..3).map(|i| a[i] * frame.axis[i + 1]).sum();
+            State([
+                u,
+                connection(&gamma, u, u) + along,
+                connection(&gamma, u, frame.axis[1]) + a[0] * u,
+                connection(&gamma, u, frame.axis[2]) + a[1] * u,
+                connection(&gamma, u, frame.axis[3]) + a[2] * u,
+            ])
+        });
+        frame = state.frame().normalize(metric);
+    }
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::metric::{CarthesianMinkowski, Schwarzschild, testing::coord};
+
+    fn at_rest() -> ManifoldFrame<CarthesianMinkowski> {
+        ManifoldFrame::from_four_velocity(
+            &CarthesianMinkowski,
+            coord([0.0; 4]),
+            FourVector([1.0, 0.0, 0.0, 0.0]),
+        )
+    }
+
+    #[test]
+    fn uniform_acceleration_follows_hyperbola() {
+        let (a, tau) = (0.5, 3.0);
+        let frame = fermi_walker(
+            &CarthesianMinkowski,
+            at_rest(),
+            |_| SpatialVec([a, 0.0, 0.0]),
+            0.0,
+            tau,
+            0.01,
+        );
+        let expected_u = [(a * tau).cosh(), (a * tau).sinh(), 0.0, 0.0];
+        let expected_x = [(a * tau).sinh(), (a * tau).cosh(), 0.0, 0.0];
+        let expected_root = [(a * tau).sinh() / a, ((a * tau).cosh() - 1.0) / a, 0.0, 0.0];
+        for i in 0..4 {
+            assert!((frame.axis[0].0[i] - expected_u[i]).abs() < 1e-8);
+            assert!((frame.axis[1].0[i] - expected_x[i]).abs() < 1e-8);
+            assert!((frame.root.components.0[i] - expected_root[i]).abs() < 1e-8);
+        }
+        // Fermi-Walker transport does not rotate the transverse axes
+        assert_eq!(frame.axis[2], FourVector([0.0, 0.0, 1.0, 0.0]));
+        assert_eq!(frame.axis[3], FourVector([0.0, 0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn geodetic_precession_over_one_orbit() {
+        let metric = Schwarzschild::new(1.0);
+        let r: f64 = 10.0;
+        let omega = (1.0 / r.powi(3)).sqrt();
+        let worldline = |t: f64| {
+            let (sin, cos) = (omega * t).sin_cos();
+            ManifoldVector {
+                root: coord([t, r * sin, 0.0, -r * cos]),
+                components: FourVector([1.0, r * omega * cos, 0.0, r * omega * sin]),
+            }
+        };
+        let start = worldline(0.0);
+        let frame = ManifoldFrame::from_four_velocity(&metric, start.root, start.components);
+        let period = 2.0 * PI / omega;
+        let transported = parallel_transport(&metric, frame, worldline, 0.0, period, 500);
+
+        // Parallel transport keeps the frame orthonormal
+        for i in 0..4 {
+            for j in 0..4 {
+                let eta = if i != j {
+                    0.0
+                } else if i == 0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+                let inner =
+                    metric.inner(transported.root, transported.axis[i], transported.axis[j]);
+                assert!((inner - eta).abs() < 1e-6, "e{i}.e{j} = {inner}");
+            }
+        }
+        let reference =
+            ManifoldFrame::from_four_velocity(&metric, transported.root, transported.axis[0]);
+        let along =
+            |i: usize| metric.inner(transported.root, transported.axis[1], reference.axis[i]);
+        let angle = along(3).atan2(along(1));
+        let expected = 2.0 * PI * (1.0 - (1.0 - 3.0 / r).sqrt());
+        assert!((angle - expected).abs() < 1e-4, "{angle} != {expected}");
+    }
+
+    #[test]
+    #[should_panic(expected = "positive and finite")]
+    fn rejects_zero_step() {
+        fermi_walker(
+            &CarthesianMinkowski,
+            at_rest(),
+            |_| SpatialVec::default(),
+            0.0,
+            1.0,
+            0.0,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "positive and finite")]
+    fn rejects_nan_step() {
+        fermi_walker(
+            &CarthesianMinkowski,
+            at_rest(),
+            |_| SpatialVec::default(),
+            0.0,
+            1.0,
+            f64::NAN,
+        );
+    }
+}
@@ -1,3 +1,73 @@
+use image::{
+    ImageError, ImageResult,
+    error::{ParameterError, ParameterErrorKind},
+};
+
 pub fn sqr(x: f64) -> f64 {
     x * x
 }
+
+/// Invert a 4x4 matrix using gauss-jordan elimination with partial pivoting
+pub fn invert4(m: [[f64; 4]; 4]) -> [[f64; 4]; 4] {
+    let mut a = m;
+    let mut inv = [[0.0; 4]; 4];
+    for (i, row) in inv.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for col in 0..4 {
+        let pivot = (col..4)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap();
+        a.swap(col, pivot);
+        inv.swap(col, pivot);
+
+        let scale = 1.0 / a[col][col];
+        for k in 0..4 {
+            a[col][k] *= scale;
+            inv[col][k] *= scale;
+        }
+
+        for row in 0..4 {
+            if row != col {
+                let factor = a[row][col];
+                for k in 0..4 {
+                    a[row][k] -= factor * a[col][k];
+                    inv[row][k] -= factor * inv[col][k];
+                }
+            }
+        }
+    }
+    inv
+}
+
+pub fn sub3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn dot3(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn normalize3(a: [f64; 3]) -> [f64; 3] {
+    let length = dot3(a, a).sqrt();
+    a.map(|c| c / length)
+}
+
+/// Fail for images without pixels, which cannot be sampled
+pub fn non_empty(width: u32, height: u32) -> ImageResult<()> {
+    if width == 0 || height == 0 {
+        return Err(ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::Generic(format!("empty {width}x{height} image")),
+        )));
+    }
+    Ok(())
+}